@@ -0,0 +1,52 @@
+#![cfg(feature = "benchmark")]
+//! counts the heap allocations of move generation. It has its own target, as the counting
+//! allocator would slow down every allocation in the timed benches.
+use athena_chess::game::{Game, MoveList};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+/// the system allocator, counting how often it is asked for memory
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// the number of allocations made while running f
+fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn main() {
+    let mut game = Game::from_fen(KIWIPETE).unwrap();
+    let mut nodes = 0;
+    let allocations = count_allocations(|| nodes = game.perft(3));
+    println!("perft 3: {nodes} nodes, {allocations} allocations");
+
+    println!(
+        "1000 generations: {} allocations into a Vec, {} into a MoveList",
+        count_allocations(|| (0..1000).for_each(|_| drop(game.get_available_moves()))),
+        count_allocations(|| (0..1000).for_each(|_| game.generate_moves_into(&mut MoveList::new())))
+    );
+}
@@ -1,13 +1,97 @@
 #![cfg(feature = "benchmark")]
-use athena_chess::game::create_tables;
-use criterion::{Criterion, criterion_group, criterion_main};
+use athena_chess::game::{BoardMask, Game, MoveList, create_tables};
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
 use std::time::Duration;
 
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
 /// profiles the speed for creating a attack pattern table
 fn bench_table_creation(c: &mut Criterion) {
     c.bench_function("create tables", |b| b.iter(|| create_tables()));
 }
 
+/// compares collecting the squares of a mask into a Vec with iterating over them directly
+fn bench_mask_iteration(c: &mut Criterion) {
+    let mask = BoardMask(0x00ff_0000_0000_ff00 | 0x0000_0018_1800_0000);
+    c.bench_function("mask as_squares", |b| {
+        b.iter(|| black_box(mask).as_squares().into_iter().map(|sq| sq.as_index()).sum::<usize>())
+    });
+    c.bench_function("mask iter", |b| {
+        b.iter(|| black_box(mask).iter().map(|sq| sq.as_index()).sum::<usize>())
+    });
+}
+
+/// profiles generating the moves of a position, handed out in a Vec by get_available_moves and
+/// written into a MoveList on the stack. The allocations they make are counted in the allocations
+/// bench.
+fn bench_move_generation(c: &mut Criterion) {
+    let start = Game::init();
+    c.bench_function("get_available_moves start", |b| b.iter(|| black_box(&start).get_available_moves()));
+    let mut game = Game::from_fen(KIWIPETE).unwrap();
+    c.bench_function("get_available_moves kiwipete", |b| {
+        b.iter(|| black_box(&game).get_available_moves())
+    });
+
+    c.bench_function("perft 3", |b| b.iter(|| black_box(&mut game).perft(3)));
+    c.bench_function("generate moves MoveList", |b| {
+        b.iter(|| {
+            let mut moves = MoveList::new();
+            black_box(&game).generate_moves_into(&mut moves);
+            moves.len()
+        })
+    });
+}
+
+/// profiles perft 4 from the start position, and compares generating the moves of every node into
+/// a MoveList with collecting them in a Vec. Both play the moves with the same unchecked make and
+/// unmake.
+fn bench_perft(c: &mut Criterion) {
+    let mut game = Game::init();
+    assert_eq!(game.perft(4), 197_281);
+    assert_eq!(game.perft_vec(4), 197_281);
+    c.bench_function("perft 4 MoveList", |b| b.iter(|| black_box(&mut game).perft(4)));
+    c.bench_function("perft 4 Vec", |b| b.iter(|| black_box(&mut game).perft_vec(4)));
+}
+
+/// perft through the public api, playing every move on a copy of the position
+fn perft_cloning(game: &Game, depth: u32) -> u64 {
+    let moves = game.get_available_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    let mut nodes = 0;
+    for mv in moves {
+        let mut next = game.clone();
+        next.apply_move_checked(mv).unwrap();
+        nodes += perft_cloning(&next, depth - 1);
+    }
+    nodes
+}
+
+/// perft through the public api, playing every move on the position itself and taking it back
+fn perft_in_place(game: &mut Game, depth: u32) -> u64 {
+    let moves = game.get_available_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    let mut nodes = 0;
+    for mv in moves {
+        game.apply_move_checked(mv).unwrap();
+        nodes += perft_in_place(game, depth - 1);
+        game.undo_move();
+    }
+    nodes
+}
+
+/// compares copying the game for every move with making and unmaking the move in place
+fn bench_make_move(c: &mut Criterion) {
+    let mut game = Game::init();
+    assert_eq!(perft_cloning(&game, 5), 4_865_609);
+    assert_eq!(perft_in_place(&mut game, 5), 4_865_609);
+    c.bench_function("perft 5 cloning", |b| b.iter(|| perft_cloning(black_box(&game), 5)));
+    c.bench_function("perft 5 in place", |b| b.iter(|| perft_in_place(black_box(&mut game), 5)));
+}
+
 /// creates the config to use for the attack pattern table creation benchmark
 fn criterion_config() -> Criterion {
     Criterion::default()
@@ -17,5 +101,5 @@ fn criterion_config() -> Criterion {
         .nresamples(1000)
 }
 
-criterion_group! {name = benches; config = criterion_config(); targets = bench_table_creation}
+criterion_group! {name = benches; config = criterion_config(); targets = bench_table_creation, bench_mask_iteration, bench_move_generation, bench_perft, bench_make_move}
 criterion_main!(benches);
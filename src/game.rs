@@ -2,19 +2,37 @@ use log::info;
 use std::sync::LazyLock;
 
 use attack_tables::AttackTables;
-use board::BitBoard;
 pub use board::piece::{Color, Piece};
 pub use board::square::*;
+use board::{BitBoard, MoveUndo, pawn_attacks};
+pub use castling::CastlingRights;
 pub use chess_move::Move;
-use error::IllegalMoveError;
+pub use cursor::GameCursor;
+use error::{FenError, IllegalMoveError, MoveParseError, PgnError, PositionError, ReplayError};
 pub use mask::BoardMask;
+pub use move_list::MoveList;
+pub use pgn::{PgnTags, format_move_list};
+use zobrist::ZOBRIST_KEYS;
 
 mod attack_tables;
 mod board;
+pub mod book;
+mod castling;
 mod chess_move;
+mod cursor;
+pub mod epd;
 mod error;
-mod evaluation;
+pub mod evaluation;
+mod fen;
 mod mask;
+mod move_generation;
+mod move_list;
+mod notation;
+mod perft;
+mod pgn;
+pub mod search;
+pub mod tablebase;
+mod zobrist;
 
 static ATTACK_TABLES: LazyLock<AttackTables> = LazyLock::new(|| {
     let start = std::time::Instant::now();
@@ -29,11 +47,78 @@ pub fn create_tables() {
     AttackTables::create_tables();
 }
 
+/// describes whether the player whos turn it is can still move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    Ongoing,
+    Checkmate,
+    Stalemate,
+}
+
+/// what a move played with Game::execute_move or Game::apply_move_checked did, so it doesn't have
+/// to be worked out again, f.e. for annotating it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MoveOutcome {
+    pub gives_check: bool,
+    pub is_mate: bool,
+    pub is_capture: bool,
+}
+
+/// how a game ended, see Game::outcome
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw(DrawReason),
+}
+
+/// why a game ended in a draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawReason {
+    Stalemate,
+    FiftyMove,
+    Threefold,
+    InsufficientMaterial,
+    Agreement,
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Game {
     board: BitBoard,
     moves: Vec<Move>,
+    // holds the information to take back each move in moves
+    undo_stack: Vec<UndoRecord>,
     turn: Color,
+    // the number of plies since the last capture or pawn move, used for the fifty-move rule
+    halfmove_clock: u32,
+    // set when the game ended by resignation or agreement instead of on the board
+    result: Option<GameResult>,
+    // the player who offered a draw that the opponent hasn't answered yet
+    draw_offer: Option<Color>,
+    // the square a pawn that just made a double move skipped, where it can be taken en passant
+    en_passant: Option<Square>,
+    // the castling moves not ruled out by an earlier move, see castling_rights
+    castling: CastlingRights,
+    // the length of moves when each null move was made and the en passant square it gave up, see
+    // make_null_move
+    null_moves: Vec<(usize, Option<Square>)>,
+    // the fullmove number of the position the game started in, taken from the fen
+    start_fullmove: u32,
+}
+
+/// everything needed to take back a move with Game::undo_move
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct UndoRecord {
+    board: MoveUndo,
+    halfmove_clock: u32,
+    en_passant: Option<Square>,
+    castling: CastlingRights,
+    // the zobrist hash of the position before the move, used to detect repetitions
+    hash: u64,
 }
 
 impl Game {
@@ -42,7 +127,438 @@ impl Game {
         Self {
             board: BitBoard::init(),
             moves: Vec::new(),
+            undo_stack: Vec::new(),
             turn: Color::White,
+            halfmove_clock: 0,
+            result: None,
+            draw_offer: None,
+            en_passant: None,
+            castling: CastlingRights::ALL,
+            null_moves: Vec::new(),
+            start_fullmove: 1,
+        }
+    }
+
+    /// plays the moves from the starting position, the reverse of move_history. Fails with the ply of
+    /// the first illegal move, counting from 0.
+    ///```
+    /// use athena_chess::game::*;
+    /// let moves = [Move::new(Piece::Pawn, E2, E4, None), Move::new(Piece::Pawn, E7, E5, None)];
+    /// let game = Game::from_moves(&moves).unwrap();
+    /// assert_eq!(game.move_history(), &moves);
+    ///```
+    pub fn from_moves(moves: &[Move]) -> Result<Self, ReplayError> {
+        let mut game = Self::init();
+        for (ply, mv) in moves.iter().enumerate() {
+            game.execute_move(*mv).map_err(|e| ReplayError::IllegalMove { ply, e })?;
+        }
+        Ok(game)
+    }
+
+    /// a game without any pieces on the board and white to move, to set up positions with place
+    ///```
+    /// use athena_chess::game::*;
+    /// let mut game = Game::empty();
+    /// game.place(Piece::King { has_moved: true }, Color::White, E1);
+    /// game.place(Piece::King { has_moved: true }, Color::Black, E8);
+    /// game.place(Piece::Queen, Color::White, D1);
+    /// assert_eq!(game.status(), GameStatus::Ongoing);
+    ///```
+    pub fn empty() -> Self {
+        Self::with_turn(Color::White)
+    }
+
+    /// a game without any pieces on the board and the given player to move
+    pub fn with_turn(turn: Color) -> Self {
+        Self {
+            board: BitBoard::empty(),
+            turn,
+            ..Self::init()
+        }
+    }
+
+    /// a game starting from the pieces on every square, a1 first, with the given player to move.
+    /// The position has to be one that could come up in a game, see validate.
+    ///```
+    /// use athena_chess::game::*;
+    /// let mut squares = [None; 64];
+    /// squares[E1.as_index()] = Some((Piece::King { has_moved: true }, Color::White));
+    /// squares[E8.as_index()] = Some((Piece::King { has_moved: true }, Color::Black));
+    /// squares[D1.as_index()] = Some((Piece::Queen, Color::White));
+    /// let game = Game::from_array(squares, Color::Black).unwrap();
+    /// assert_eq!(game.piece_at(D1), Some((Piece::Queen, Color::White)));
+    /// assert_eq!(game.turn(), Color::Black);
+    ///```
+    pub fn from_array(squares: [Option<(Piece, Color)>; 64], turn: Color) -> Result<Self, PositionError> {
+        Ok(Self {
+            board: BitBoard::from_array(squares, turn)?,
+            ..Self::with_turn(turn)
+        })
+    }
+
+    /// checks that the position is one that could come up in a game, see BitBoard::validate. Positions
+    /// built piece by piece with place aren't checked on their own.
+    ///```
+    /// use athena_chess::game::*;
+    /// assert!(Game::init().validate().is_ok());
+    /// let mut game = Game::init();
+    /// game.place(Piece::Pawn, Color::White, D8);
+    /// assert!(game.validate().is_err());
+    ///```
+    pub fn validate(&self) -> Result<(), PositionError> {
+        self.board.validate(self.turn)
+    }
+
+    /// the position with the board mirrored, see BitBoard::mirror, and the other player to move. The
+    /// en passant square and the castling rights are mirrored as well, the moves that lead to the
+    /// position aren't kept.
+    ///```
+    /// use athena_chess::game::*;
+    /// let mut game = Game::init();
+    /// game.execute_move(Move::new(Piece::Pawn, E2, E4, None)).unwrap();
+    /// let mirrored = game.mirror();
+    /// assert_eq!(mirrored.turn(), Color::White);
+    /// assert_eq!(mirrored.piece_at(E5), Some((Piece::Pawn, Color::Black)));
+    ///```
+    pub fn mirror(&self) -> Self {
+        Self {
+            board: self.board.mirror(),
+            halfmove_clock: self.halfmove_clock,
+            // the skipped square moves to the same file on the other side of the board
+            en_passant: self
+                .en_passant
+                .map(|square| Square::new(square.as_u8() ^ 56).expect("a flipped square is still on the board")),
+            castling: self.castling.mirror(),
+            ..Self::with_turn(!self.turn)
+        }
+    }
+
+    /// puts the piece on the square, replacing the piece that stood there, which is returned.
+    pub fn place(&mut self, piece: Piece, color: Color, square: Square) -> Option<(Piece, Color)> {
+        self.board.place_piece_on_square(piece, color, square)
+    }
+
+    /// the piece standing on square and its color, if there is one
+    ///```
+    /// use athena_chess::game::*;
+    /// let game = Game::init();
+    /// assert_eq!(game.piece_at(E2), Some((Piece::Pawn, Color::White)));
+    /// assert_eq!(game.piece_at(E4), None);
+    ///```
+    pub fn piece_at(&self, square: Square) -> Option<(Piece, Color)> {
+        self.board.get_piece_on_square(square).copied()
+    }
+
+    /// the player whos turn it is
+    pub fn turn(&self) -> Color {
+        self.turn
+    }
+
+    /// the castling moves that are still allowed. Besides the rights lost by earlier moves this
+    /// also leaves out the ones whose king or rook isn't on its starting square unmoved, f.e. in a
+    /// position set up with place.
+    ///```
+    /// use athena_chess::game::*;
+    /// let game = Game::from_fen("r3k3/8/8/8/8/8/8/R3K2R w KQq - 0 1").unwrap();
+    /// assert_eq!(game.castling_rights(), CastlingRights::WK | CastlingRights::WQ | CastlingRights::BQ);
+    /// assert_eq!(Game::empty().castling_rights(), CastlingRights::NONE);
+    ///```
+    pub fn castling_rights(&self) -> CastlingRights {
+        let mut rights = self.castling;
+        for (color, rank) in [(Color::White, Rank::One), (Color::Black, Rank::Eight)] {
+            let unmoved =
+                |piece: Piece, file: File| self.board.get_piece_on_square(Square::from_rank_file(rank, file)) == Some(&(piece, color));
+            let king = unmoved(Piece::King { has_moved: false }, File::E);
+            if !king || !unmoved(Piece::Rook { has_moved: false }, File::H) {
+                rights.remove(CastlingRights::kingside(color));
+            }
+            if !king || !unmoved(Piece::Rook { has_moved: false }, File::A) {
+                rights.remove(CastlingRights::queenside(color));
+            }
+        }
+        rights
+    }
+
+    /// the moves played so far, the first move first
+    pub fn move_history(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// returns true if no pawn was moved and no piece was taken in the last fifty moves of each
+    /// player, so either player can claim a draw
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    /// returns true if the current position occured for the third time, with the same player to
+    /// move, the same castling rights and the same en passant capture. Only positions since the last capture or pawn move are
+    /// looked at, as the ones before can't come back.
+    pub fn is_threefold_repetition(&self) -> bool {
+        let hash = self.zobrist_hash();
+        let earlier = self
+            .undo_stack
+            .iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .filter(|undo| undo.hash == hash)
+            .count();
+        earlier >= 2
+    }
+
+    /// returns true if the current position occured before since the last capture or pawn move.
+    /// The search scores these positions as draws, as whoever repeated them once can do so again.
+    fn is_repetition(&self) -> bool {
+        let hash = self.zobrist_hash();
+        self.undo_stack
+            .iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .any(|undo| undo.hash == hash)
+    }
+
+    /// returns true if neither player has enough pieces left to checkmate: only kings, a king and a
+    /// single knight or bishop against a king, or only bishops which all stand on the same color.
+    pub fn is_insufficient_material(&self) -> bool {
+        let pieces = |piece| self.board.pieces(piece, Color::White) | self.board.pieces(piece, Color::Black);
+        let heavy = pieces(Piece::Pawn) | pieces(Piece::Rook { has_moved: true }) | pieces(Piece::Queen);
+        if heavy.count_ones() > 0 {
+            return false;
+        }
+        let knights = pieces(Piece::Knight);
+        let bishops = pieces(Piece::Bishop);
+        match (knights.count_ones(), bishops.count_ones()) {
+            (0, 0) | (1, 0) | (0, 1) => true,
+            (0, _) => {
+                let mut colors = bishops.iter().map(|sq| sq.square_color());
+                let first = colors.next();
+                colors.all(|color| Some(color) == first)
+            }
+            _ => false,
+        }
+    }
+
+    /// the result of the game, or None if it is still going on. Checkmate and stalemate come from
+    /// the position, the fifty move rule and threefold repetitions are treated as claimed right
+    /// away.
+    ///```
+    /// use athena_chess::game::*;
+    /// let game = Game::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    /// assert_eq!(game.outcome(), Some(GameResult::WhiteWins));
+    /// assert_eq!(Game::init().outcome(), None);
+    ///```
+    pub fn outcome(&self) -> Option<GameResult> {
+        if self.result.is_some() {
+            return self.result;
+        }
+        match self.status() {
+            GameStatus::Checkmate if self.turn.is_white() => Some(GameResult::BlackWins),
+            GameStatus::Checkmate => Some(GameResult::WhiteWins),
+            GameStatus::Stalemate => Some(GameResult::Draw(DrawReason::Stalemate)),
+            GameStatus::Ongoing if self.is_fifty_move_draw() => Some(GameResult::Draw(DrawReason::FiftyMove)),
+            GameStatus::Ongoing if self.is_threefold_repetition() => Some(GameResult::Draw(DrawReason::Threefold)),
+            GameStatus::Ongoing if self.is_insufficient_material() => Some(GameResult::Draw(DrawReason::InsufficientMaterial)),
+            GameStatus::Ongoing => None,
+        }
+    }
+
+    /// ends the game with a win for the opponent of color. Does nothing if the game is already over.
+    ///```
+    /// use athena_chess::game::*;
+    /// let mut game = Game::init();
+    /// game.resign(Color::White);
+    /// assert_eq!(game.outcome(), Some(GameResult::BlackWins));
+    ///```
+    pub fn resign(&mut self, color: Color) {
+        if self.outcome().is_none() {
+            self.result = Some(if color.is_white() {
+                GameResult::BlackWins
+            } else {
+                GameResult::WhiteWins
+            });
+        }
+    }
+
+    /// offers a draw to the opponent of color. The offer stands until the opponent accepts it or
+    /// makes a move.
+    pub fn offer_draw(&mut self, color: Color) {
+        if self.outcome().is_none() {
+            self.draw_offer = Some(color);
+        }
+    }
+
+    /// accepts the draw the opponent of color offered, ending the game. Returns false if there is no
+    /// such offer.
+    ///```
+    /// use athena_chess::game::*;
+    /// let mut game = Game::init();
+    /// assert!(!game.accept_draw(Color::Black));
+    /// game.offer_draw(Color::White);
+    /// assert!(game.accept_draw(Color::Black));
+    /// assert_eq!(game.outcome(), Some(GameResult::Draw(DrawReason::Agreement)));
+    ///```
+    pub fn accept_draw(&mut self, color: Color) -> bool {
+        if self.draw_offer != Some(!color) || self.outcome().is_some() {
+            return false;
+        }
+        self.draw_offer = None;
+        self.result = Some(GameResult::Draw(DrawReason::Agreement));
+        true
+    }
+
+    /// the zobrist hash of the position, which is equal for equal positions no matter how they were
+    /// reached
+    pub fn zobrist_hash(&self) -> u64 {
+        let hash = match self.turn {
+            Color::White => self.board.hash(),
+            Color::Black => self.board.hash() ^ ZOBRIST_KEYS.black_to_move(),
+        };
+        match self.en_passant_file() {
+            Some(file) => hash ^ ZOBRIST_KEYS.en_passant(file),
+            None => hash,
+        }
+    }
+
+    /// the file of the en passant square if a pawn of the side to move stands next to the pawn
+    /// that just moved. Otherwise the position is the same as without the square, so it isn't
+    /// hashed.
+    pub(crate) fn en_passant_file(&self) -> Option<File> {
+        let square = self.en_passant_square()?;
+        let capturers = pawn_attacks(BoardMask::default().with_square(square), !self.turn) & self.board.pieces(Piece::Pawn, self.turn);
+        (capturers.count_ones() > 0).then(|| square.get_file())
+    }
+
+    /// the zobrist hash of the position after mv, computed from the hash of the current position
+    /// without playing the move, f.e. to look up the position in the transposition table before
+    /// making the move. The move has to be pseudo legal.
+    ///```
+    /// use athena_chess::game::*;
+    /// let mut game = Game::init();
+    /// let mv = Move::new(Piece::Knight, G1, F3, None);
+    /// let hash = game.hash_after(&mv);
+    /// game.execute_move(mv).unwrap();
+    /// assert_eq!(game.zobrist_hash(), hash);
+    ///```
+    pub fn hash_after(&self, mv: &Move) -> u64 {
+        let mut hash = self.zobrist_hash() ^ ZOBRIST_KEYS.black_to_move();
+        if let Some(file) = self.en_passant_file() {
+            hash ^= ZOBRIST_KEYS.en_passant(file);
+        }
+        let (from, to) = (mv.get_from(), mv.get_to());
+        let Some(&(piece, color)) = self.board.get_piece_on_square(from) else {
+            return hash;
+        };
+        // the same changes as BitBoard::make_move, applied to the hash only
+        let mut moved = if piece.is_pawn() {
+            mv.get_promotion().unwrap_or(piece)
+        } else {
+            piece
+        };
+        moved.make_moved();
+        hash ^= ZOBRIST_KEYS.piece(piece, color, from) ^ ZOBRIST_KEYS.piece(moved, color, to);
+
+        let delta_file = from.get_delta_file(to);
+        if let Some(&(taken, taken_color)) = self.board.get_piece_on_square(to) {
+            hash ^= ZOBRIST_KEYS.piece(taken, taken_color, to);
+        } else if piece.is_pawn() && delta_file != 0 {
+            let taken_sq = Square::from_rank_file(from.get_rank(), to.get_file());
+            if let Some(&(taken, taken_color)) = self.board.get_piece_on_square(taken_sq) {
+                hash ^= ZOBRIST_KEYS.piece(taken, taken_color, taken_sq);
+            }
+        }
+        if piece.is_king() && delta_file.abs() == 2 {
+            let rook_sq = Square::from_rank_file(from.get_rank(), if delta_file > 0 { File::H } else { File::A });
+            if let Some(&(rook, rook_color)) = self.board.get_piece_on_square(rook_sq) {
+                let mut moved_rook = rook;
+                moved_rook.make_moved();
+                let rook_to = from.move_on_rank(delta_file / 2).unwrap();
+                hash ^= ZOBRIST_KEYS.piece(rook, rook_color, rook_sq) ^ ZOBRIST_KEYS.piece(moved_rook, rook_color, rook_to);
+            }
+        }
+        // a double pawn move next to an enemy pawn gives it an en passant capture
+        if piece.is_pawn() && from.get_delta_rank(to).abs() == 2 {
+            let skipped = from.move_on_file(from.get_delta_rank(to) / 2).unwrap();
+            let beside = pawn_attacks(BoardMask::default().with_square(skipped), color) & self.board.pieces(Piece::Pawn, !color);
+            if beside.count_ones() > 0 {
+                hash ^= ZOBRIST_KEYS.en_passant(to.get_file());
+            }
+        }
+        hash
+    }
+
+    /// draws the board as text, see BitBoard::render
+    pub fn to_ascii(&self) -> String {
+        self.board.render(false)
+    }
+
+    /// draws the board with chess glyphs, see BitBoard::render
+    pub fn to_unicode(&self) -> String {
+        self.board.render(true)
+    }
+
+    /// returns true if the king of the player whos turn it is is attacked, false if there is no king
+    pub fn is_in_check(&self) -> bool {
+        self.board
+            .king_square(self.turn)
+            .is_some_and(|king| self.board.square_is_controlled_by(king, !self.turn))
+    }
+
+    /// returns true if a piece of color attacks the square, no matter whos turn it is. Pins are
+    /// ignored, so a pinned piece still attacks the squares it could move to.
+    ///```
+    /// use athena_chess::game::*;
+    /// let mut game = Game::init();
+    /// // f7 is only defended by the black king
+    /// assert!(game.is_square_attacked(F7, Color::Black));
+    /// assert!(!game.is_square_attacked(F7, Color::White));
+    /// for san in ["e4", "e5", "Bc4"] {
+    ///     game.execute_move(game.parse_san_move(san).unwrap()).unwrap();
+    /// }
+    /// assert!(game.is_square_attacked(F7, Color::White));
+    ///```
+    pub fn is_square_attacked(&self, square: Square, by: Color) -> bool {
+        self.board.square_is_controlled_by(square, by)
+    }
+
+    /// the squares of all pieces of color attacking the square, no matter whos turn it is. Like
+    /// is_square_attacked this ignores pins.
+    ///```
+    /// use athena_chess::game::*;
+    /// let attackers = Game::init().attackers_of(F3, Color::White);
+    /// assert_eq!(attackers.as_squares(), vec![G1, E2, G2]);
+    ///```
+    pub fn attackers_of(&self, square: Square, by: Color) -> BoardMask {
+        self.board.attackers_through(square, by, self.board.occupancy)
+    }
+
+    /// checks whether the player whos turn it is is checkmated or stalemated
+    pub fn status(&self) -> GameStatus {
+        if !self.get_available_moves().is_empty() {
+            GameStatus::Ongoing
+        } else if self.is_in_check() {
+            GameStatus::Checkmate
+        } else {
+            GameStatus::Stalemate
+        }
+    }
+
+    /// like outcome, but only for checkmate and stalemate and with the legal moves already
+    /// generated, so the search doesn't generate them twice. Check is only looked at if there are
+    /// no moves. Returns None if there are moves.
+    ///```
+    /// use athena_chess::game::*;
+    /// let game = Game::from_fen("7k/5K2/6Q1/8/8/8/8/8 b - - 0 1").unwrap();
+    /// let moves = game.get_available_moves();
+    /// assert_eq!(game.terminal_state(&moves), Some(GameResult::Draw(DrawReason::Stalemate)));
+    ///```
+    pub fn terminal_state(&self, moves: &[Move]) -> Option<GameResult> {
+        if !moves.is_empty() {
+            None
+        } else if !self.is_in_check() {
+            Some(GameResult::Draw(DrawReason::Stalemate))
+        } else if self.turn.is_white() {
+            Some(GameResult::BlackWins)
+        } else {
+            Some(GameResult::WhiteWins)
         }
     }
 
@@ -85,33 +601,19 @@ impl Game {
 
             // takes to the right
             if from.get_delta_file(to) == 1 {
-                // if the previous move was a double pawn move on the file that we are moving
-                // to and it put the pawn next to us.
-                if self.moves.last().is_some_and(|m| {
-                    // the previous move was a double move on the file which we want to take on
-                    m.get_piece() == Piece::Pawn
-                        && m.get_from().get_delta_rank(m.get_to()).abs() == 2
-                        && m.get_from().get_file() == to.get_file()
-                        && m.get_to().get_rank() == from.get_rank()
-                }) {
+                // the previous move was a double pawn move skipping the square we move to
+                if self.en_passant_square() == Some(to) {
                     info!("en-pasent");
-                    self.board.remove_piece_from_square(self.moves.last().unwrap().get_to());
-                    mv.set_takes(Some(Piece::Pawn));
+                    *mv = mv.with_capture(Piece::Pawn);
                 } else if self.board.get_piece_on_square(to).is_none() {
                     return Err(IllegalMoveError::TakesEmptySquare { mv: *mv, square: to });
                 }
             }
             // takes to the left
             if from.get_delta_file(to) == -1 {
-                if self.moves.last().is_some_and(|m| {
-                    m.get_piece() == Piece::Pawn
-                        && m.get_from().get_delta_rank(m.get_to()).abs() == 2
-                        && m.get_from().get_file() == to.get_file()
-                        && m.get_to().get_rank() == from.get_rank()
-                }) {
+                if self.en_passant_square() == Some(to) {
                     info!("en-pasent");
-                    self.board.remove_piece_from_square(self.moves.last().unwrap().get_to());
-                    mv.set_takes(Some(Piece::Pawn));
+                    *mv = mv.with_capture(Piece::Pawn);
                 } else if self.board.get_piece_on_square(to).is_none() {
                     return Err(IllegalMoveError::TakesEmptySquare { mv: *mv, square: to });
                 }
@@ -121,7 +623,7 @@ impl Game {
     }
     fn short_castle(&mut self, from: Square, mv: Move) -> Result<(), IllegalMoveError> {
         let rook_sq = Square::from_rank_file(from.get_rank(), File::H);
-        if let Some((pc, col)) = self.board.get_piece_on_square_mut(rook_sq) {
+        if let Some((pc, col)) = self.board.get_piece_on_square(rook_sq) {
             if *col != self.turn {
                 return Err(IllegalMoveError::NotYourPiece {
                     color: *col,
@@ -149,10 +651,7 @@ impl Game {
                         return Err(IllegalMoveError::MoveInvalid { mv });
                     }
 
-                    let (mut rook, col) = self.board.remove_piece_from_square(rook_sq).unwrap();
-                    rook.make_moved();
-
-                    self.board.place_piece_on_square(rook, col, f);
+                    // the rook is moved together with the king in BitBoard::make_move
                     Ok(())
                 }
                 Piece::Rook { has_moved } if *has_moved => Err(IllegalMoveError::MoveInvalid { mv }),
@@ -197,8 +696,7 @@ impl Game {
                         return Err(IllegalMoveError::MoveInvalid { mv });
                     }
 
-                    let (rook, col) = self.board.remove_piece_from_square(rook_sq).unwrap();
-                    self.board.place_piece_on_square(rook, col, d);
+                    // the rook is moved together with the king in BitBoard::make_move
                     Ok(())
                 }
                 Piece::Rook { has_moved } if *has_moved => Err(IllegalMoveError::MoveInvalid { mv }),
@@ -212,7 +710,19 @@ impl Game {
         }
     }
 
-    pub fn execute_move(&mut self, mut mv: Move) -> Result<(), IllegalMoveError> {
+    /// plays the move for the player whos turn it is and tells whether it took a piece, gave check
+    /// or mated. The move has to be legal, otherwise the game stays unchanged and the reason is
+    /// returned as an error.
+    ///```
+    /// use athena_chess::game::*;
+    /// let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    /// let outcome = game.execute_move(Move::new(Piece::Rook { has_moved: true }, A1, A8, None)).unwrap();
+    /// assert!(outcome.gives_check && outcome.is_mate && !outcome.is_capture);
+    ///```
+    pub fn execute_move(&mut self, mut mv: Move) -> Result<MoveOutcome, IllegalMoveError> {
+        if self.result.is_some() {
+            return Err(IllegalMoveError::GameOver);
+        }
         let from = mv.get_from();
         let to = mv.get_to();
 
@@ -233,6 +743,18 @@ impl Game {
             return Err(IllegalMoveError::NotYourPiece { color: c, square: from });
         }
 
+        // only pawns reaching the last rank promote and they have to promote to a knight, bishop,
+        // rook or queen
+        let last_rank = Rank::promotion_rank(self.turn);
+        let promotes = p.is_pawn() && to.get_rank() == last_rank;
+        match mv.get_promotion() {
+            Some(promotion) if !promotes || promotion.is_pawn() || promotion.is_king() => {
+                return Err(IllegalMoveError::MoveInvalid { mv });
+            }
+            None if promotes => return Err(IllegalMoveError::MoveInvalid { mv }),
+            _ => (),
+        }
+
         // filter out all moves that would take own piece
         if self.board.get_piece_on_square(to).is_some_and(|(_, col)| *col == c) {
             return Err(IllegalMoveError::TakesOwnPiece {
@@ -261,7 +783,7 @@ impl Game {
                     // moved
                     if has_moved || from.get_delta_rank(to) != 0 {
                         return Err(IllegalMoveError::MoveInvalid { mv });
-                    } else if from.get_delta_file(to) == -3 {
+                    } else if from.get_delta_file(to) == -2 {
                         // long castle
                         self.long_castle(from, mv)?;
                     } else if from.get_delta_file(to) == 2 {
@@ -298,15 +820,141 @@ impl Game {
             }
         }
 
-        let (mut temp_p, temp_c) = self.board.remove_piece_from_square(from).expect("checked that from is Some");
-        temp_p.make_moved();
+        // the checks above only cover how the piece moves, so the move also has to be one of the
+        // generated moves, which catches everything they miss and moves into check
+        let same_promotion =
+            |m: &Move| m.get_promotion().as_ref().map(std::mem::discriminant) == mv.get_promotion().as_ref().map(std::mem::discriminant);
+        let Some(generated) = self
+            .pseudo_legal_moves_from(from, p)
+            .iter()
+            .copied()
+            .find(|m| m.get_to() == to && same_promotion(m))
+        else {
+            // the king isn't even offered the squares it would be in check on
+            if p.is_king() && !self.is_legal(&mv) {
+                return Err(IllegalMoveError::IsInCheck);
+            }
+            return Err(IllegalMoveError::MoveInvalid { mv });
+        };
+        if !self.is_legal(&generated) {
+            return Err(IllegalMoveError::IsInCheck);
+        }
 
-        let takes = self.board.place_piece_on_square(temp_p, temp_c, to).map(|(taken, _)| taken);
+        self.apply_move(mv);
+        Ok(self.last_move_outcome())
+    }
 
-        mv.set_takes(takes);
+    /// plays the move if it is one of the legal moves in the current position. Unlike
+    /// execute_move this doesn't tell why a move is rejected, every illegal move is reported as
+    /// MoveInvalid. Once the game is over every move is rejected with GameOver.
+    pub fn apply_move_checked(&mut self, mv: Move) -> Result<MoveOutcome, IllegalMoveError> {
+        if self.result.is_some() {
+            return Err(IllegalMoveError::GameOver);
+        }
+        let legal = self.get_available_moves().into_iter().find(|m| m.matches(&mv));
+        match legal {
+            Some(legal) => {
+                self.apply_move(legal);
+                Ok(self.last_move_outcome())
+            }
+            None => Err(IllegalMoveError::MoveInvalid { mv }),
+        }
+    }
+
+    /// what the move played last did, looked at from the position after it
+    fn last_move_outcome(&self) -> MoveOutcome {
+        let gives_check = self.is_in_check();
+        MoveOutcome {
+            gives_check,
+            is_mate: gives_check && self.get_available_moves().is_empty(),
+            is_capture: self.moves.last().is_some_and(Move::is_capture),
+        }
+    }
+
+    /// plays the move without checking whether it is valid. This is the fast path used by
+    /// execute_move and apply_move_checked after validating the move and by the search and perft
+    /// for moves that were generated and so are known to be legal.
+    fn apply_move(&mut self, mut mv: Move) {
+        let hash = self.zobrist_hash();
+        let undo = self.board.make_move(&mv);
+        self.undo_stack.push(UndoRecord {
+            board: undo,
+            halfmove_clock: self.halfmove_clock,
+            en_passant: self.en_passant,
+            castling: self.castling,
+            hash,
+        });
+        self.castling
+            .remove(CastlingRights::lost_on(mv.get_from()) | CastlingRights::lost_on(mv.get_to()));
+        // a double move can be taken en passant on the square the pawn skipped, but only right away
+        let (from, delta) = (mv.get_from(), mv.get_from().get_delta_rank(mv.get_to()));
+        self.en_passant = (mv.get_piece().is_pawn() && delta.abs() == 2).then(|| from.move_on_file(delta / 2).unwrap());
+
+        // the clock is reset on every capture and pawn move
+        if mv.get_piece().is_pawn() || undo.taken.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if let Some(((taken, _), _)) = undo.taken {
+            mv = mv.with_capture(taken);
+        }
+        // moving declines the draw the opponent offered
+        if self.draw_offer == Some(!self.turn) {
+            self.draw_offer = None;
+        }
         self.moves.push(mv);
         self.turn = !self.turn;
-        Ok(())
+    }
+
+    /// passes the turn to the opponent without moving a piece, as used by null move pruning in the
+    /// search. A pawn that just made a double move can't be taken en passant after it. The player
+    /// to move must not be in check. It is taken back with undo_null_move, or by undo_move along
+    /// with the move before it.
+    ///```
+    /// use athena_chess::game::*;
+    /// let mut game = Game::init();
+    /// let hash = game.zobrist_hash();
+    /// game.make_null_move();
+    /// assert_ne!(game.zobrist_hash(), hash);
+    /// game.undo_null_move();
+    /// assert_eq!(game.zobrist_hash(), hash);
+    ///```
+    pub fn make_null_move(&mut self) {
+        self.null_moves.push((self.moves.len(), self.en_passant.take()));
+        self.turn = !self.turn;
+    }
+
+    /// takes back the null move made last. Does nothing if the last move wasn't a null move.
+    pub fn undo_null_move(&mut self) {
+        if self.last_move_was_null()
+            && let Some((_, en_passant)) = self.null_moves.pop()
+        {
+            self.en_passant = en_passant;
+            self.turn = !self.turn;
+        }
+    }
+
+    /// whether no move was made since the last null move
+    fn last_move_was_null(&self) -> bool {
+        self.null_moves.last().is_some_and(|&(len, _)| len == self.moves.len())
+    }
+
+    /// takes back the last move and returns it. Null moves made after it are taken back first.
+    /// Returns None if no move was made yet.
+    pub fn undo_move(&mut self) -> Option<Move> {
+        while self.last_move_was_null() {
+            self.undo_null_move();
+        }
+        let mv = self.moves.pop()?;
+        let undo = self.undo_stack.pop().expect("every move has an undo record");
+        self.board.unmake_move(&mv, &undo.board);
+        self.halfmove_clock = undo.halfmove_clock;
+        self.en_passant = undo.en_passant;
+        self.castling = undo.castling;
+        self.turn = !self.turn;
+        Some(mv)
     }
 }
 
@@ -314,6 +962,229 @@ impl Game {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_start_position_status() {
+        let game = Game::init();
+        assert!(!game.is_in_check());
+        assert_eq!(game.status(), GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn test_back_rank_mate() {
+        let mut game = Game::empty();
+        game.place(Piece::King { has_moved: true }, Color::White, G1);
+        game.place(Piece::Pawn, Color::White, F2);
+        game.place(Piece::Pawn, Color::White, G2);
+        game.place(Piece::Pawn, Color::White, H2);
+        game.place(Piece::King { has_moved: true }, Color::Black, G8);
+        game.place(Piece::Rook { has_moved: true }, Color::Black, E1);
+        assert!(game.is_in_check());
+        assert_eq!(game.status(), GameStatus::Checkmate);
+
+        // with an escape square it is just check
+        game.board.remove_piece_from_square(H2);
+        assert!(game.is_in_check());
+        assert_eq!(game.status(), GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn test_stalemate() {
+        let mut game = Game::empty();
+        game.place(Piece::King { has_moved: true }, Color::Black, A8);
+        game.place(Piece::Queen, Color::White, B6);
+        game.place(Piece::King { has_moved: true }, Color::White, A6);
+        game.turn = Color::Black;
+        assert!(!game.is_in_check());
+        assert_eq!(game.status(), GameStatus::Stalemate);
+    }
+
+    #[test]
+    fn test_undo_move() {
+        let mut game = Game::init();
+        let start = game.clone();
+        assert_eq!(game.undo_move(), None);
+
+        let mv = Move::new(Piece::Knight, G1, F3, None);
+        game.execute_move(mv).unwrap();
+        assert_eq!(game.undo_move(), Some(mv));
+        assert_eq!(game.board, start.board);
+        assert_eq!(game.turn, Color::White);
+    }
+
+    #[test]
+    fn test_undo_capture() {
+        let mut game = Game::init();
+        game.execute_move(Move::new(Piece::Pawn, E2, E4, None)).unwrap();
+        game.execute_move(Move::new(Piece::Pawn, D7, D5, None)).unwrap();
+        let before = game.clone();
+        game.execute_move(Move::new(Piece::Pawn, E4, D5, None)).unwrap();
+        assert_eq!(game.undo_move(), Some(Move::new(Piece::Pawn, E4, D5, Some(Piece::Pawn))));
+        assert_eq!(game.board, before.board);
+        assert_eq!(game.turn, Color::White);
+    }
+
+    #[test]
+    fn test_undo_en_passant() {
+        let mut game = Game::init();
+        game.execute_move(Move::new(Piece::Pawn, E2, E4, None)).unwrap();
+        game.execute_move(Move::new(Piece::Pawn, A7, A6, None)).unwrap();
+        game.execute_move(Move::new(Piece::Pawn, E4, E5, None)).unwrap();
+        game.execute_move(Move::new(Piece::Pawn, D7, D5, None)).unwrap();
+        let before = game.clone();
+        game.execute_move(Move::new(Piece::Pawn, E5, D6, None)).unwrap();
+        assert!(game.board.get_piece_on_square(D5).is_none());
+        game.undo_move();
+        assert_eq!(game.board, before.board);
+        assert_eq!(game.board.get_piece_on_square(D5), Some(&(Piece::Pawn, Color::Black)));
+    }
+
+    #[test]
+    fn test_undo_castling() {
+        let mut game = Game::empty();
+        game.place(Piece::King { has_moved: false }, Color::White, E1);
+        game.place(Piece::Rook { has_moved: false }, Color::White, H1);
+        game.place(Piece::Rook { has_moved: false }, Color::White, A1);
+        game.place(Piece::King { has_moved: false }, Color::Black, E8);
+        let before = game.clone();
+        for to in [G1, C1] {
+            game.execute_move(Move::new(Piece::King { has_moved: false }, E1, to, None))
+                .unwrap();
+            game.undo_move();
+            assert_eq!(game.board, before.board);
+        }
+    }
+
+    #[test]
+    fn test_undo_restores_has_moved_only_for_first_move() {
+        let mut game = Game::empty();
+        game.place(Piece::King { has_moved: false }, Color::White, E1);
+        game.place(Piece::Rook { has_moved: false }, Color::White, H1);
+        game.place(Piece::King { has_moved: false }, Color::Black, E8);
+        game.execute_move(Move::new(Piece::Rook { has_moved: false }, H1, H2, None))
+            .unwrap();
+        game.execute_move(Move::new(Piece::King { has_moved: false }, E8, E7, None))
+            .unwrap();
+        game.execute_move(Move::new(Piece::Rook { has_moved: true }, H2, H1, None)).unwrap();
+        // the rook is back on its square but has moved, undoing the last move keeps it that way
+        game.undo_move();
+        assert_eq!(
+            game.board.get_piece_on_square(H2),
+            Some(&(Piece::Rook { has_moved: true }, Color::White))
+        );
+        game.undo_move();
+        game.undo_move();
+        assert_eq!(
+            game.board.get_piece_on_square(H1),
+            Some(&(Piece::Rook { has_moved: false }, Color::White))
+        );
+        assert_eq!(
+            game.board.get_piece_on_square(E8),
+            Some(&(Piece::King { has_moved: false }, Color::Black))
+        );
+    }
+
+    #[test]
+    fn test_undo_all_generated_moves() {
+        let mut game = Game::init();
+        for mv in [
+            Move::new(Piece::Pawn, E2, E4, None),
+            Move::new(Piece::Pawn, D7, D5, None),
+            Move::new(Piece::Pawn, E4, D5, None),
+            Move::new(Piece::Knight, G8, F6, None),
+        ] {
+            game.execute_move(mv).unwrap();
+        }
+        let before = game.clone();
+        for mv in game.get_available_moves() {
+            game.execute_move(mv).unwrap();
+            game.undo_move();
+            assert_eq!(game.board, before.board, "undoing {mv} changed the board");
+        }
+    }
+
+    #[test]
+    fn test_halfmove_clock() {
+        let mut game = Game::init();
+        game.execute_move(Move::new(Piece::Knight, G1, F3, None)).unwrap();
+        game.execute_move(Move::new(Piece::Knight, G8, F6, None)).unwrap();
+        assert_eq!(game.halfmove_clock, 2);
+        // pawn moves reset the clock
+        game.execute_move(Move::new(Piece::Pawn, E2, E4, None)).unwrap();
+        assert_eq!(game.halfmove_clock, 0);
+        game.execute_move(Move::new(Piece::Knight, B8, C6, None)).unwrap();
+        assert_eq!(game.halfmove_clock, 1);
+        // and so do captures
+        game.execute_move(Move::new(Piece::Knight, F3, E5, None)).unwrap();
+        game.execute_move(Move::new(Piece::Knight, C6, E5, None)).unwrap();
+        assert_eq!(game.halfmove_clock, 0);
+
+        game.undo_move();
+        assert_eq!(game.halfmove_clock, 2);
+        game.undo_move();
+        assert_eq!(game.halfmove_clock, 1);
+    }
+
+    #[test]
+    fn test_fifty_move_draw() {
+        let mut game = Game::from_fen("8/8/8/8/8/8/8/K6k w - - 99 80").unwrap();
+        assert!(!game.is_fifty_move_draw());
+        game.execute_move(Move::new(Piece::King { has_moved: true }, A1, A2, None)).unwrap();
+        assert!(game.is_fifty_move_draw());
+        game.undo_move();
+        assert!(!game.is_fifty_move_draw());
+    }
+
+    #[test]
+    fn test_promotion() {
+        let mut game = Game::from_fen("k7/4P3/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        let before = game.clone();
+        assert!(game.execute_move(Move::new(Piece::Pawn, E7, E8, None)).is_err());
+        let [queen, rook, bishop, knight] = Move::promotions(E7, E8, None);
+        assert!(game.execute_move(knight).is_ok());
+        assert_eq!(game.board.get_piece_on_square(E8), Some(&(Piece::Knight, Color::White)));
+        game.undo_move();
+        assert_eq!(game.board, before.board);
+        for mv in [queen, rook, bishop] {
+            assert!(game.execute_move(mv).is_ok());
+            assert_eq!(game.board.get_piece_on_square(E8).unwrap().0, mv.get_promotion().unwrap());
+            game.undo_move();
+        }
+    }
+
+    #[test]
+    fn test_invalid_promotion() {
+        // pawns can only promote on the last rank
+        let mut game = Game::from_fen("k7/8/8/8/8/8/4P3/K7 w - - 0 1").unwrap();
+        assert!(game.execute_move(Move::promotions(E2, E3, None)[0]).is_err());
+        assert!(game.execute_move(Move::new(Piece::Pawn, E2, E3, None)).is_ok());
+    }
+
+    #[test]
+    fn test_promotion_capture() {
+        let mut game = Game::from_fen("k2r4/4P3/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        let before = game.clone();
+        let mv = Move::promotion(E7, D8, Some(Piece::Rook { has_moved: false }), Piece::Queen);
+        let outcome = game.execute_move(mv).unwrap();
+        assert!(outcome.is_capture && outcome.gives_check);
+        assert_eq!(game.board.get_piece_on_square(D8), Some(&(Piece::Queen, Color::White)));
+        assert_eq!(game.board.count(Piece::Rook { has_moved: true }, Color::Black), 0);
+        assert_eq!(game.board.occupancy_for(Color::Black).count(), 1);
+        game.undo_move();
+        assert_eq!(game.board, before.board);
+    }
+
+    #[test]
+    fn test_only_pawns_promote() {
+        // a queen stands where the move expects the pawn
+        let mut game = Game::from_fen("k7/4Q3/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        let mv = Move::promotion(E7, E8, None, Piece::Knight);
+        assert!(matches!(game.execute_move(mv), Err(IllegalMoveError::DifferentPiece { .. })));
+        assert!(game.apply_move_checked(mv).is_err());
+        // the unchecked path moves the queen without turning it into a knight
+        game.apply_move(mv);
+        assert_eq!(game.board.get_piece_on_square(E8), Some(&(Piece::Queen, Color::White)));
+    }
+
     #[test]
     fn test_pawn_double_move() {
         let mut game = Game::init();
@@ -330,6 +1201,376 @@ mod test {
         assert!(game.execute_move(mv).is_err());
     }
 
+    #[test]
+    fn test_execute_move_rejections() {
+        let mut game = Game::init();
+        let mv = Move::new(Piece::Pawn, E3, E4, None);
+        assert_eq!(game.execute_move(mv), Err(IllegalMoveError::EmptySquare { square: E3 }));
+        let mv = Move::new(Piece::Knight, E2, E4, None);
+        assert_eq!(
+            game.execute_move(mv),
+            Err(IllegalMoveError::DifferentPiece {
+                expected: Piece::Knight,
+                found: Piece::Pawn
+            })
+        );
+        let mv = Move::new(Piece::Pawn, E7, E5, None);
+        assert_eq!(
+            game.execute_move(mv),
+            Err(IllegalMoveError::NotYourPiece {
+                color: Color::Black,
+                square: E7
+            })
+        );
+        let mv = Move::new(Piece::Knight, G1, E2, None);
+        assert_eq!(
+            game.execute_move(mv),
+            Err(IllegalMoveError::TakesOwnPiece { mv, piece: Piece::Pawn })
+        );
+        let mv = Move::new(Piece::Pawn, E2, D3, None);
+        assert_eq!(game.execute_move(mv), Err(IllegalMoveError::TakesEmptySquare { mv, square: D3 }));
+        let mv = Move::new(Piece::Bishop, C1, E3, None);
+        assert_eq!(game.execute_move(mv), Err(IllegalMoveError::MoveInvalid { mv }));
+        game.place(Piece::Pawn, Color::Black, E3);
+        let mv = Move::new(Piece::Pawn, E2, E4, None);
+        assert_eq!(game.execute_move(mv), Err(IllegalMoveError::Blocked { mv, square: E3 }));
+
+        // only pawns on their starting rank may move two squares and no pawn moves sideways
+        let mut game = Game::from_fen("4k3/8/8/8/8/4P3/8/4K3 w - - 0 1").unwrap();
+        let mv = Move::new(Piece::Pawn, E3, E5, None);
+        assert_eq!(game.execute_move(mv), Err(IllegalMoveError::MoveInvalid { mv }));
+        let mv = Move::new(Piece::Pawn, E3, B4, None);
+        assert_eq!(game.execute_move(mv), Err(IllegalMoveError::MoveInvalid { mv }));
+
+        // a pinned piece can't move and the king can't walk into check
+        let mut game = Game::from_fen("4k3/3rr3/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+        let mv = Move::new(Piece::Knight, E2, C3, None);
+        assert_eq!(game.execute_move(mv), Err(IllegalMoveError::IsInCheck));
+        let mv = Move::new(Piece::King { has_moved: true }, E1, D1, None);
+        assert_eq!(game.execute_move(mv), Err(IllegalMoveError::IsInCheck));
+    }
+
+    #[test]
+    fn test_move_outcome() {
+        // back rank mate
+        let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+        let mate = game.parse_uci_move("a1a8").unwrap();
+        assert_eq!(
+            game.execute_move(mate),
+            Ok(MoveOutcome {
+                gives_check: true,
+                is_mate: true,
+                is_capture: false
+            })
+        );
+
+        let mut game = Game::from_fen("r5k1/5p1p/6p1/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+        let check = game.parse_uci_move("a1a8").unwrap();
+        assert_eq!(
+            game.apply_move_checked(check),
+            Ok(MoveOutcome {
+                gives_check: true,
+                is_mate: false,
+                is_capture: true
+            })
+        );
+        assert_eq!(game.execute_move(game.parse_uci_move("g8g7").unwrap()), Ok(MoveOutcome::default()));
+    }
+
+    #[test]
+    fn test_apply_move_checked() {
+        let mut game = Game::init();
+        // the bishop can't jump over its own pawn
+        let mv = Move::new(Piece::Bishop, C1, G5, None);
+        assert_eq!(game.apply_move_checked(mv), Err(IllegalMoveError::MoveInvalid { mv }));
+        assert_eq!(game.board, Game::init().board);
+
+        game.apply_move_checked(Move::new(Piece::Pawn, D2, D4, None)).unwrap();
+        game.apply_move_checked(Move::new(Piece::Pawn, E7, E5, None)).unwrap();
+        game.apply_move_checked(Move::new(Piece::Bishop, C1, G5, None)).unwrap();
+        // the capture is filled in from the generated move
+        game.apply_move_checked(Move::new(Piece::Pawn, E5, D4, None)).unwrap();
+        assert_eq!(game.moves[3].get_takes(), Some(Piece::Pawn));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut game = Game::init();
+        for mv in ["e2e4", "d7d5", "e4d5", "g8f6", "f1b5", "c7c6", "g1f3", "c6b5", "e1g1"] {
+            game.execute_move(game.parse_uci_move(mv).unwrap()).unwrap();
+        }
+        let json = serde_json::to_string(&game).unwrap();
+        let mut loaded: Game = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.board, game.board);
+        assert_eq!(loaded.moves, game.moves);
+        assert_eq!(loaded.turn, game.turn);
+        assert_eq!(loaded.zobrist_hash(), game.zobrist_hash());
+        assert_eq!(loaded.get_available_moves(), game.get_available_moves());
+
+        // the history survives, so the moves can still be taken back
+        while loaded.undo_move().is_some() {}
+        assert_eq!(loaded.board, BitBoard::init());
+
+        assert!(serde_json::from_str::<BitBoard>("[null]").is_err());
+        assert!(serde_json::from_str::<Square>("64").is_err());
+    }
+
+    #[test]
+    fn test_outcome() {
+        let game = Game::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(game.outcome(), Some(GameResult::WhiteWins));
+        let game = Game::from_fen("8/8/8/8/8/6k1/6q1/7K w - - 0 1").unwrap();
+        assert_eq!(game.outcome(), Some(GameResult::BlackWins));
+        let game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(game.outcome(), Some(GameResult::Draw(DrawReason::Stalemate)));
+        let game = Game::from_fen("7k/8/6K1/8/8/8/8/R7 b - - 100 80").unwrap();
+        assert_eq!(game.outcome(), Some(GameResult::Draw(DrawReason::FiftyMove)));
+        let game = Game::from_fen("7k/8/6K1/8/8/8/8/7B b - - 0 1").unwrap();
+        assert_eq!(game.outcome(), Some(GameResult::Draw(DrawReason::InsufficientMaterial)));
+        assert_eq!(Game::init().outcome(), None);
+    }
+
+    #[test]
+    fn test_no_moves_after_resignation() {
+        let mut game = Game::init();
+        game.execute_move(Move::new(Piece::Pawn, E2, E4, None)).unwrap();
+        game.resign(Color::Black);
+        assert_eq!(game.outcome(), Some(GameResult::WhiteWins));
+        let mv = Move::new(Piece::Pawn, E7, E5, None);
+        assert_eq!(game.execute_move(mv), Err(IllegalMoveError::GameOver));
+        assert_eq!(game.apply_move_checked(mv), Err(IllegalMoveError::GameOver));
+
+        // the game can't end a second time
+        game.resign(Color::White);
+        game.offer_draw(Color::White);
+        assert!(!game.accept_draw(Color::Black));
+        assert_eq!(game.outcome(), Some(GameResult::WhiteWins));
+    }
+
+    #[test]
+    fn test_draw_offer_is_declined_by_moving() {
+        let mut game = Game::init();
+        game.offer_draw(Color::White);
+        game.execute_move(Move::new(Piece::Pawn, E2, E4, None)).unwrap();
+        // the offer stands while the opponent thinks
+        game.execute_move(Move::new(Piece::Pawn, E7, E5, None)).unwrap();
+        assert!(!game.accept_draw(Color::Black));
+
+        game.offer_draw(Color::White);
+        // a player can't accept their own offer
+        assert!(!game.accept_draw(Color::White));
+        assert!(game.accept_draw(Color::Black));
+        assert_eq!(game.outcome(), Some(GameResult::Draw(DrawReason::Agreement)));
+        assert_eq!(
+            game.execute_move(Move::new(Piece::Knight, G1, F3, None)),
+            Err(IllegalMoveError::GameOver)
+        );
+    }
+
+    #[test]
+    fn test_from_moves() {
+        let mut played = Game::init();
+        for san in ["d4", "d5", "c4", "e6", "Nc3", "Nf6", "Bg5", "Be7"] {
+            played.execute_move(played.parse_san_move(san).unwrap()).unwrap();
+        }
+        let game = Game::from_moves(played.move_history()).unwrap();
+        let expected = Game::from_fen("rnbqk2r/ppp1bppp/4pn2/3p2B1/2PP4/2N5/PP2PPPP/R2QKBNR w KQkq - 4 5").unwrap();
+        assert_eq!(game.board, expected.board);
+        assert_eq!(game.turn, expected.turn);
+        assert_eq!(game.halfmove_clock, expected.halfmove_clock);
+        assert_eq!(game.move_history(), played.move_history());
+
+        let moves = [
+            Move::new(Piece::Pawn, E2, E4, None),
+            Move::new(Piece::Pawn, E7, E5, None),
+            Move::new(Piece::Pawn, E4, E5, None),
+        ];
+        assert_eq!(
+            Game::from_moves(&moves).unwrap_err(),
+            ReplayError::IllegalMove {
+                ply: 2,
+                e: IllegalMoveError::MoveInvalid { mv: moves[2] }
+            }
+        );
+    }
+
+    #[test]
+    fn test_null_move() {
+        let mut game = Game::init();
+        for mv in ["e2e4", "a7a6", "e4e5", "d7d5"] {
+            game.execute_move(game.parse_uci_move(mv).unwrap()).unwrap();
+        }
+        let en_passant = Move::new(Piece::Pawn, E5, D6, Some(Piece::Pawn));
+        assert!(game.get_available_moves().contains(&en_passant));
+        let hash = game.zobrist_hash();
+        let moves = game.get_available_moves();
+
+        // passing gives up the en passant capture, which is part of the hash
+        let without_en_passant = hash ^ ZOBRIST_KEYS.en_passant(File::D);
+        game.make_null_move();
+        assert_eq!(game.turn, Color::Black);
+        assert_eq!(game.zobrist_hash(), without_en_passant ^ ZOBRIST_KEYS.black_to_move());
+        // when black passes too, the pawn on d5 still just made its double move, but white has
+        // given up the right to take it en passant
+        game.make_null_move();
+        assert_eq!(game.zobrist_hash(), without_en_passant);
+        assert!(!game.get_available_moves().contains(&en_passant));
+        game.undo_null_move();
+
+        game.undo_null_move();
+        assert_eq!(game.zobrist_hash(), hash);
+        assert_eq!(game.get_available_moves(), moves);
+        // there is no null move left to take back
+        game.undo_null_move();
+        assert_eq!(game.turn, Color::White);
+    }
+
+    #[test]
+    fn test_undo_move_takes_back_null_moves() {
+        let mut game = Game::init();
+        game.execute_move(game.parse_uci_move("e2e4").unwrap()).unwrap();
+        let after_e4 = game.to_fen();
+        let e5 = game.parse_uci_move("e7e5").unwrap();
+        game.execute_move(e5).unwrap();
+        game.make_null_move();
+        game.make_null_move();
+        game.make_null_move();
+        assert_eq!(game.undo_move(), Some(e5));
+        assert_eq!(game.to_fen(), after_e4);
+        game.undo_move();
+        game.make_null_move();
+        assert_eq!(game.undo_move(), None);
+        assert_eq!(game.to_fen(), Game::init().to_fen());
+    }
+
+    #[test]
+    fn test_threefold_repetition() {
+        let mut game = Game::init();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        for mv in shuffle.iter().chain(&shuffle) {
+            assert_eq!(game.outcome(), None);
+            game.execute_move(game.parse_uci_move(mv).unwrap()).unwrap();
+        }
+        // the start position occured for the third time
+        assert!(game.is_threefold_repetition());
+        assert_eq!(game.outcome(), Some(GameResult::Draw(DrawReason::Threefold)));
+        game.undo_move();
+        assert!(!game.is_threefold_repetition());
+    }
+
+    #[test]
+    fn test_repetition_after_en_passant_chance() {
+        // after e4 black can take en passant, so the position isn't the same as the ones after the
+        // knights went back and forth
+        let mut game = Game::from_fen("4k1n1/8/8/8/3p4/8/4P3/4K1N1 w - - 0 1").unwrap();
+        let shuffle = ["g8f6", "g1f3", "f6g8", "f3g1"];
+        let play = |game: &mut Game, moves: &[&str]| {
+            for mv in moves {
+                game.execute_move(game.parse_uci_move(mv).unwrap()).unwrap();
+            }
+        };
+        play(&mut game, &["e2e4"]);
+        play(&mut game, &shuffle);
+        play(&mut game, &shuffle);
+        assert!(!game.is_threefold_repetition());
+        assert_eq!(game.outcome(), None);
+        play(&mut game, &shuffle);
+        assert_eq!(game.outcome(), Some(GameResult::Draw(DrawReason::Threefold)));
+    }
+
+    #[test]
+    fn test_capturing_rook_removes_castling_right() {
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/6b1/R3K2R b KQkq - 0 1").unwrap();
+        let takes = Move::new(Piece::Bishop, G2, H1, Some(Piece::Rook { has_moved: false }));
+        game.execute_move(takes).unwrap();
+        assert_eq!(game.castling_rights(), CastlingRights::WQ | CastlingRights::BK | CastlingRights::BQ);
+        game.undo_move();
+        assert_eq!(game.castling_rights(), CastlingRights::ALL);
+    }
+
+    #[test]
+    fn test_rook_returning_home_cant_castle() {
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        for uci in ["h1h2", "a8a7", "h2h1", "a7a8"] {
+            game.execute_move(game.parse_uci_move(uci).unwrap()).unwrap();
+        }
+        let short = Move::new(Piece::King { has_moved: false }, E1, G1, None);
+        assert_eq!(game.castling_rights(), CastlingRights::WQ | CastlingRights::BK);
+        assert!(game.execute_move(short).is_err());
+
+        // the fen can't tell the rooks moved, only the castling field does
+        let mut game = Game::from_fen(&game.to_fen()).unwrap();
+        assert_eq!(game.castling_rights(), CastlingRights::WQ | CastlingRights::BK);
+        assert!(game.execute_move(short).is_err());
+        assert!(game.execute_move(Move::new(Piece::King { has_moved: false }, E1, C1, None)).is_ok());
+    }
+
+    #[test]
+    fn test_attackers_of() {
+        // the bishop on b5 pins the knight, which still attacks e5
+        let game = Game::from_fen("r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3").unwrap();
+        assert_eq!(game.attackers_of(E5, Color::White).as_squares(), vec![F3]);
+        assert_eq!(game.attackers_of(E5, Color::Black).as_squares(), vec![C6]);
+        assert_eq!(game.attackers_of(C6, Color::White).as_squares(), vec![B5]);
+        assert_eq!(game.attackers_of(D7, Color::Black).as_squares(), vec![C8, D8, E8]);
+        assert!(game.attackers_of(H5, Color::White).as_squares().is_empty());
+    }
+
+    #[test]
+    fn test_hash_after() {
+        // quiet moves, captures, castling both ways, en passant and promotions with and without
+        // capture
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "4k3/8/8/8/3p1p2/8/4P3/4K3 w - - 0 1",
+            "4k3/4p3/8/3P1P2/8/8/8/4K3 b - - 0 1",
+            "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+            "n1n1k3/PPP5/8/8/8/8/ppp5/N1N1K3 w - - 0 1",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            for mv in game.get_available_moves() {
+                let mut next = game.clone();
+                next.execute_move(mv).unwrap();
+                assert_eq!(game.hash_after(&mv), next.zobrist_hash(), "{fen} {mv}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_terminal_state() {
+        // in check, but the king can take the queen
+        let game = Game::from_fen("7k/6Q1/8/6K1/8/8/8/8 b - - 0 1").unwrap();
+        assert!(game.is_in_check());
+        assert_eq!(game.terminal_state(&game.get_available_moves()), None);
+        assert_eq!(Game::init().terminal_state(&Game::init().get_available_moves()), None);
+        // the list isn't checked against the position
+        let mate = Game::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(mate.terminal_state(&Game::init().get_available_moves()), None);
+        assert_eq!(mate.terminal_state(&[]), Some(GameResult::WhiteWins));
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        for (fen, insufficient) in [
+            ("7k/8/6K1/8/8/8/8/8 w - - 0 1", true),
+            ("7k/8/6K1/8/8/8/8/6N1 w - - 0 1", true),
+            ("7k/8/6K1/8/8/8/8/6b1 w - - 0 1", true),
+            // bishops on the same color
+            ("7k/8/6K1/8/8/8/8/2B3b1 w - - 0 1", true),
+            // bishops on opposite colors can still mate
+            ("7k/8/6K1/8/8/8/8/2B4b w - - 0 1", false),
+            ("7k/8/6K1/8/8/8/8/2N3N1 w - - 0 1", false),
+            ("7k/8/6K1/8/8/8/8/2N3b1 w - - 0 1", false),
+            ("7k/8/6K1/8/8/8/P7/8 w - - 0 1", false),
+            ("7k/8/6K1/8/8/8/8/r7 w - - 0 1", false),
+        ] {
+            assert_eq!(Game::from_fen(fen).unwrap().is_insufficient_material(), insufficient, "{fen}");
+        }
+    }
+
     #[test]
     fn test_knight_move() {
         let mut game = Game::init();
@@ -342,7 +1583,7 @@ mod test {
     #[test]
     fn test_blocked_pawn_move() {
         let mut game = Game::init();
-        game.board.place_piece_on_square(Piece::Pawn, Color::Black, E3);
+        game.place(Piece::Pawn, Color::Black, E3);
         let mv = Move::new(Piece::Pawn, E2, E4, None);
         assert!(game.execute_move(mv).is_err());
     }
@@ -390,6 +1631,36 @@ mod test {
         assert!(game.execute_move(en_passant_move).is_ok()); // This should be a valid move in a real game
     }
 
+    #[test]
+    fn test_en_passant_matrix() {
+        // the position, the double pawn move, the en passant capture, the square of the taken pawn
+        // and a pawn next to the capturing one that has to stay on the board
+        let cases = [
+            ("4k3/3p4/8/4Pp2/8/8/8/4K3 b - - 0 1", (D7, D5), (E5, D6), D5, F5),
+            ("4k3/5p2/8/3pP3/8/8/8/4K3 b - - 0 1", (F7, F5), (E5, F6), F5, D5),
+            ("4k3/8/8/8/3pP3/8/2P5/4K3 w - - 0 1", (C2, C4), (D4, C3), C4, E4),
+            ("4k3/8/8/8/2Pp4/8/4P3/4K3 w - - 0 1", (E2, E4), (D4, E3), E4, C4),
+        ];
+        for (fen, (double_from, double_to), (from, to), taken, other) in cases {
+            let mut game = Game::from_fen(fen).unwrap();
+            game.execute_move(Move::new(Piece::Pawn, double_from, double_to, None)).unwrap();
+            let color = game.turn;
+            let en_passant = Move::new(Piece::Pawn, from, to, Some(Piece::Pawn));
+            assert!(game.get_available_moves().contains(&en_passant), "{fen}");
+
+            let before = game.board.clone();
+            game.execute_move(en_passant).unwrap();
+            assert_eq!(game.board.get_piece_on_square(to), Some(&(Piece::Pawn, color)), "{fen}");
+            assert_eq!(game.board.get_piece_on_square(from), None, "{fen}");
+            assert_eq!(game.board.get_piece_on_square(taken), None, "{fen}");
+            assert_eq!(game.board.get_piece_on_square(other), before.get_piece_on_square(other), "{fen}");
+            assert!(game.board.get_piece_on_square(other).is_some(), "{fen}");
+
+            game.undo_move();
+            assert_eq!(game.board, before, "{fen}");
+        }
+    }
+
     #[test]
     fn test_short_castle_white() {
         let mut game = Game::init();
@@ -419,8 +1690,8 @@ mod test {
         game.board.remove_piece_from_square(F1);
         game.board.remove_piece_from_square(G1);
         game.board.remove_piece_from_square(E2);
-        game.board
-            .place_piece_on_square(Piece::Rook { has_moved: false }, Color::Black, E8);
+        game.board.remove_piece_from_square(E7);
+        game.place(Piece::Rook { has_moved: false }, Color::Black, E8);
         let mv = Move::new(Piece::King { has_moved: false }, E1, G1, None);
         assert_eq!(game.execute_move(mv), Err(IllegalMoveError::IsInCheck));
     }
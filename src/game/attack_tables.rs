@@ -3,14 +3,16 @@ use super::board::square::*;
 
 use super::mask::BoardMask;
 use attack_magic::AttackMagic;
-use move_logic::create_knight_attack_pattern;
+#[cfg(feature = "validate-magics")]
+use move_logic::{create_bishop_attack_pattern, create_rook_attack_pattern};
+use move_logic::{create_king_attack_pattern, create_knight_attack_pattern};
 
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 mod attack_magic;
-mod move_logic;
+pub(super) mod move_logic;
 
-/// hold the attack tables for rook, bishop and knight, which are precomputed at engine startup.
+/// hold the attack tables for rook, bishop, knight and king, which are precomputed at engine startup.
 /// During board evaluation, getting all possible moves for a piece is as simple as 2 pointer
 /// lookups or ~200 ns
 #[derive(Debug, Clone)]
@@ -18,16 +20,18 @@ pub struct AttackTables {
     pub rook_tables: [AttackMagic; 64],
     pub bishop_tables: [AttackMagic; 64],
     pub knight_table: [BoardMask; 64],
+    pub king_table: [BoardMask; 64],
 }
 
 impl AttackTables {
-    /// parralelized computes magic values and tables for sliding pieces as well as a simple Table for the
-    /// knight
+    /// parralelized computes the tables for sliding pieces from the precomputed magic values (or
+    /// searches new ones with the magic-search feature) as well as simple Tables for the knight and
+    /// the king
     pub fn create_tables() -> Self {
         // note the use of par_iter, so we can compute all 64 at the same time
         let mut bishop_vec: Vec<Option<AttackMagic>> = (0..64)
             .into_par_iter()
-            .map(|i| Some(AttackMagic::create_attack_magic_bishop(Square::new(i).unwrap())))
+            .map(|i| Some(AttackMagic::bishop(Square::new(i).unwrap())))
             .collect();
 
         // as [_;64] can't be constructed from an Iterator, we manually move the elements over.
@@ -37,7 +41,7 @@ impl AttackTables {
 
         let mut rook_vec: Vec<Option<AttackMagic>> = (0..64)
             .into_par_iter()
-            .map(|i| Some(AttackMagic::create_attack_magic_rook(Square::new(i).unwrap())))
+            .map(|i| Some(AttackMagic::rook(Square::new(i).unwrap())))
             .collect();
         let rook_tables: [AttackMagic; 64] = core::array::from_fn(|i| rook_vec[i].take().unwrap());
 
@@ -47,24 +51,44 @@ impl AttackTables {
             .collect();
         let knight_table: [BoardMask; 64] = core::array::from_fn(|i| knight_vec[i].take().unwrap());
 
+        let mut king_vec: Vec<Option<BoardMask>> = (0..64)
+            .into_par_iter()
+            .map(|i| Some(create_king_attack_pattern(Square::new(i).unwrap())))
+            .collect();
+        let king_table: [BoardMask; 64] = core::array::from_fn(|i| king_vec[i].take().unwrap());
+
         Self {
             rook_tables,
             bishop_tables,
             knight_table,
+            king_table,
         }
     }
     /// retrieves the pattern describing all attacked squares for a rook standing at square with
     /// the given occupancy of the board
     pub fn get_attack_pattern_rook(&self, square: Square, occupancy: Occupancy) -> BoardMask {
         let attack_magic = &self.rook_tables[square.as_index()];
-        attack_magic.attack_patterns[occupancy.hash(attack_magic.mask, attack_magic.magic_number, attack_magic.shift)]
+        let pattern = attack_magic.attack_patterns[attack_magic.index(occupancy)];
+        #[cfg(feature = "validate-magics")]
+        assert_eq!(
+            pattern,
+            create_rook_attack_pattern(square, occupancy.with_square_removed(square)),
+            "wrong rook attacks on {square}"
+        );
+        pattern
     }
     /// retrieves the pattern describing all attacked squares for a bishop standing at square with
     /// the given occupancy of the board
     pub fn get_attack_pattern_bishop(&self, square: Square, occupancy: Occupancy) -> BoardMask {
         let attack_magic = &self.bishop_tables[square.as_index()];
-        // we need to
-        attack_magic.attack_patterns[occupancy.hash(attack_magic.mask, attack_magic.magic_number, attack_magic.shift)]
+        let pattern = attack_magic.attack_patterns[attack_magic.index(occupancy)];
+        #[cfg(feature = "validate-magics")]
+        assert_eq!(
+            pattern,
+            create_bishop_attack_pattern(square, occupancy.with_square_removed(square)),
+            "wrong bishop attacks on {square}"
+        );
+        pattern
     }
     /// retrieves the pattern describing all attacked squares for a Queen standing at square with
     /// the given occupancy of the board by adding the patterns of the Rook and bishop together
@@ -76,4 +100,9 @@ impl AttackTables {
     pub fn get_attack_pattern_knight(&self, square: Square) -> BoardMask {
         self.knight_table[square.as_index()]
     }
+    /// retrieves the pattern describing all attacked squares for a king standing at square.
+    /// Castling is not part of the pattern, as it depends on the state of the game.
+    pub fn get_attack_pattern_king(&self, square: Square) -> BoardMask {
+        self.king_table[square.as_index()]
+    }
 }
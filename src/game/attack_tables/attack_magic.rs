@@ -1,4 +1,6 @@
+#[cfg(feature = "magic-search")]
 use log::info;
+#[cfg(feature = "magic-search")]
 use rand::{self, random};
 
 use super::move_logic::*;
@@ -8,6 +10,7 @@ use crate::game::board::square::Square;
 
 /// the density with which the arrays will be paced. Increasing this will result in more sparsely
 /// populated arrays but faster times for finding magic numbers
+#[cfg(feature = "magic-search")]
 pub const H: u32 = 1;
 
 #[derive(Debug, Clone, Default)]
@@ -22,62 +25,134 @@ pub struct AttackMagic {
     // the Occupancie. So we get a direct Mapping from currently occupied squares and our current
     // square, to all available moves.
     pub attack_patterns: Vec<BoardMask>,
+    // whether attack_patterns is indexed with pext instead of the magic hash, only set if the cpu
+    // supports bmi2
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    pub pext: bool,
 }
 
 impl AttackMagic {
+    /// the attack magic for a rook on square. The magic number is searched for at startup with the
+    /// magic-search feature and taken from ROOK_MAGICS otherwise.
+    pub fn rook(square: Square) -> Self {
+        #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+        if let Some(magic) = Self::from_pext(create_rook_mask(square), |occ| create_rook_attack_pattern(square, occ)) {
+            return magic;
+        }
+        #[cfg(feature = "magic-search")]
+        return Self::create_attack_magic_rook(square);
+        #[cfg(not(feature = "magic-search"))]
+        {
+            let (magic_number, shift) = ROOK_MAGICS[square.as_index()];
+            Self::from_const_rook(square, magic_number, shift)
+        }
+    }
+
+    /// the attack magic for a bishop on square. The magic number is searched for at startup with
+    /// the magic-search feature and taken from BISHOP_MAGICS otherwise.
+    pub fn bishop(square: Square) -> Self {
+        #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+        if let Some(magic) = Self::from_pext(create_bishop_mask(square), |occ| create_bishop_attack_pattern(square, occ)) {
+            return magic;
+        }
+        #[cfg(feature = "magic-search")]
+        return Self::create_attack_magic_bishop(square);
+        #[cfg(not(feature = "magic-search"))]
+        {
+            let (magic_number, shift) = BISHOP_MAGICS[square.as_index()];
+            Self::from_const_bishop(square, magic_number, shift)
+        }
+    }
+
     /// creates magic numbers and computes the attack patterns for the given square
+    #[cfg(feature = "magic-search")]
     pub fn create_attack_magic_rook(square: Square) -> Self {
         info!("creating rook magic for {square}");
         let mask = create_rook_mask(square);
-
         let required_bits = (mask.0.count_ones() + H) as u8;
-        let len = 2_usize.pow(required_bits as u32);
-        let shift = 64 - required_bits;
-
-        let occupancies = occupancies_from_mask(mask);
-        let magic_number = find_valid_magic_number(mask, len, &occupancies);
-
-        let mut attack_patterns: Vec<BoardMask> = vec![BoardMask(0); len];
-        occupancies
-            .iter()
-            .map(|occ| (occ.hash(mask, magic_number, shift), create_rook_attack_pattern(square, *occ)))
-            .for_each(|(h, t)| attack_patterns[h] = t);
-
-        Self {
-            mask,
-            magic_number,
-            shift,
-            attack_patterns,
-        }
+        let magic_number = find_valid_magic_number(mask, 2_usize.pow(required_bits as u32), &occupancies_from_mask(mask));
+        Self::from_const_rook(square, magic_number, 64 - required_bits)
     }
 
     /// creates magic numbers and computes the attack patterns for the given square
+    #[cfg(feature = "magic-search")]
     pub fn create_attack_magic_bishop(square: Square) -> Self {
         info!("creating bishop magic for {square}");
         let mask = create_bishop_mask(square);
-
         let required_bits = (mask.0.count_ones() + H) as u8;
-        let len = 2_usize.pow(required_bits as u32);
-        let shift = 64 - required_bits;
+        let magic_number = find_valid_magic_number(mask, 2_usize.pow(required_bits as u32), &occupancies_from_mask(mask));
+        Self::from_const_bishop(square, magic_number, 64 - required_bits)
+    }
 
-        let occupancies = occupancies_from_mask(mask);
-        let magic_number = find_valid_magic_number(mask, len, &occupancies);
+    /// computes the attack patterns for a rook on square with an already known magic number and
+    /// shift. The magic number has to hash all occupancies of the mask without collisions.
+    pub fn from_const_rook(square: Square, magic_number: u64, shift: u8) -> Self {
+        let mask = create_rook_mask(square);
+        Self::from_const(mask, magic_number, shift, |occ| create_rook_attack_pattern(square, occ))
+    }
 
-        let mut attack_patterns: Vec<BoardMask> = vec![BoardMask(0); len];
-        occupancies
+    /// computes the attack patterns for a bishop on square with an already known magic number and
+    /// shift. The magic number has to hash all occupancies of the mask without collisions.
+    pub fn from_const_bishop(square: Square, magic_number: u64, shift: u8) -> Self {
+        let mask = create_bishop_mask(square);
+        Self::from_const(mask, magic_number, shift, |occ| create_bishop_attack_pattern(square, occ))
+    }
+
+    fn from_const(mask: BoardMask, magic_number: u64, shift: u8, pattern: impl Fn(Occupancy) -> BoardMask) -> Self {
+        let mut attack_patterns: Vec<BoardMask> = vec![BoardMask(0); 1 << (64 - shift)];
+        occupancies_from_mask(mask)
             .iter()
-            .map(|occ| (occ.hash(mask, magic_number, shift), create_bishop_attack_pattern(square, *occ)))
-            .for_each(|(h, t)| attack_patterns[h] = t);
+            .for_each(|occ| attack_patterns[occ.hash(mask, magic_number, shift)] = pattern(*occ));
 
         Self {
             mask,
             magic_number,
             shift,
             attack_patterns,
+            #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+            pext: false,
+        }
+    }
+
+    /// fills the attack patterns densely, indexed by the occupancy bits under the mask extracted
+    /// with pext. Returns None if the cpu doesn't support bmi2.
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    fn from_pext(mask: BoardMask, pattern: impl Fn(Occupancy) -> BoardMask) -> Option<Self> {
+        if !std::arch::is_x86_feature_detected!("bmi2") {
+            return None;
+        }
+        let mut attack_patterns: Vec<BoardMask> = vec![BoardMask(0); 1 << mask.count_ones()];
+        for occ in occupancies_from_mask(mask) {
+            // SAFETY: we just checked that the cpu supports bmi2
+            attack_patterns[unsafe { pext(occ.0, mask.0) } as usize] = pattern(occ);
+        }
+        Some(Self {
+            mask,
+            magic_number: 0,
+            shift: 0,
+            attack_patterns,
+            pext: true,
+        })
+    }
+
+    /// the index into attack_patterns for the given occupancy of the board
+    pub fn index(&self, occupancy: Occupancy) -> usize {
+        #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+        if self.pext {
+            // SAFETY: pext is only set by from_pext, after checking that the cpu supports bmi2
+            return unsafe { pext(occupancy.0, self.mask.0) } as usize;
         }
+        occupancy.hash(self.mask, self.magic_number, self.shift)
     }
 }
 
+/// extracts the bits of value under mask into the low bits of the result
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+#[target_feature(enable = "bmi2")]
+fn pext(value: u64, mask: u64) -> u64 {
+    std::arch::x86_64::_pext_u64(value, mask)
+}
+
 impl Occupancy {
     pub const fn hash(&self, mask: BoardMask, magic_number: u64, shift: u8) -> usize {
         // we mask off only the relevant squares (so f.e. for a rook that would be the horizontal and
@@ -107,8 +182,9 @@ fn occupancies_from_mask(mask: BoardMask) -> Vec<Occupancy> {
 }
 
 /// finds a valid magic number so the hash over all possible occupancies for a given mask is
-/// bijective. This method uses try and error and is highly resource intensive. If possible should
-/// use pre-computed magic values and load them from disk
+/// bijective. This method uses try and error and is highly resource intensive, which is why the
+/// results are committed as ROOK_MAGICS and BISHOP_MAGICS.
+#[cfg(feature = "magic-search")]
 fn find_valid_magic_number(mask: BoardMask, arr_size: usize, occupancies: &Vec<Occupancy>) -> u64 {
     // the shift is used to select the appropriate amount of msbs for a given array size to index
     // into.
@@ -131,15 +207,229 @@ fn find_valid_magic_number(mask: BoardMask, arr_size: usize, occupancies: &Vec<O
     }
 }
 
+/// magic numbers and shifts for the rook on each square, found by find_valid_magic_number. Can be
+/// regenerated with: cargo test --features magic-search print_magic_constants -- --ignored --nocapture
+#[rustfmt::skip]
+#[cfg_attr(feature = "magic-search", allow(unused))]
+pub const ROOK_MAGICS: [(u64, u8); 64] = [
+    (0xf5ffef7c401d0203, 51),
+    (0x7e00093cc4e5ca70, 52),
+    (0x18fd8bb7d684e000, 52),
+    (0x894441c944222160, 52),
+    (0xd0f0f1000145268b, 52),
+    (0xddde96223025de82, 52),
+    (0x87e4fde33f3e40bf, 52),
+    (0x1855a38754957501, 51),
+    (0x674a4423220e6383, 52),
+    (0xf23e0013d1faff81, 53),
+    (0x770692c060dfccce, 53),
+    (0x3a4e7b38bd6e2e7c, 53),
+    (0xbdf8f683dc87093a, 53),
+    (0xe0a32414974b2424, 53),
+    (0xc207e5524cd9890e, 53),
+    (0xd5b1fff52c812bea, 52),
+    (0x9ac75a2bffea0577, 52),
+    (0x5f7e8de00562f477, 53),
+    (0x90999125ad6296b4, 53),
+    (0x9a3b8a445e2915bc, 53),
+    (0x885c8a6ba06a6b71, 53),
+    (0x72e907ff5eaec283, 53),
+    (0xe81590dfc78f0273, 53),
+    (0xafd2c9dd21031ca7, 52),
+    (0xc475aa655744aba0, 52),
+    (0x7cb1e760e64e8778, 53),
+    (0x6c9035b92c284a92, 53),
+    (0xcfbaf25ffdc6cf20, 53),
+    (0x9a56a626a62ec3d9, 53),
+    (0x5c436ee49112284b, 53),
+    (0x08a2fca4c493b594, 53),
+    (0x9271c777120dfb3d, 52),
+    (0xa586b8b9a0fa77be, 52),
+    (0xe9618618989cdb34, 53),
+    (0x28235d28ae7aa5e5, 53),
+    (0xf95cd0201cd02a04, 53),
+    (0x980a87814a88015e, 53),
+    (0x1c90c2efb50fdaed, 53),
+    (0x5ae8d1c9967e4e4d, 53),
+    (0x018e519acf71117a, 52),
+    (0xc6e9924280a9c000, 52),
+    (0x66375c9142cfbffa, 53),
+    (0xdc8ba57f5ffe567d, 53),
+    (0x52eca4ddad6251a5, 53),
+    (0x68741a8ae5f2d2d3, 53),
+    (0xbc6268400218401d, 53),
+    (0xac062694010e4008, 53),
+    (0x6375984801a38009, 52),
+    (0x738edb30e93534bc, 52),
+    (0x0fe962b221424748, 53),
+    (0xb8e47484906001e8, 53),
+    (0x19d8aad026859600, 53),
+    (0x919f7f9903439001, 53),
+    (0x1d43a7bd3fe9f540, 53),
+    (0x4ef404e3ff68260c, 53),
+    (0xae4b7c030f2e3580, 52),
+    (0x09fea221268e4f66, 51),
+    (0xb44f87f760624cf2, 52),
+    (0xe259d260473d3f42, 52),
+    (0x6bb876d3c4d5ff9e, 52),
+    (0x2db116957600053e, 52),
+    (0xd653ae0035118c16, 52),
+    (0x1476a0bf8572766c, 52),
+    (0x0343ac0256e01022, 51),
+];
+
+/// magic numbers and shifts for the bishop on each square, see ROOK_MAGICS
+#[rustfmt::skip]
+#[cfg_attr(feature = "magic-search", allow(unused))]
+pub const BISHOP_MAGICS: [(u64, u8); 64] = [
+    (0x495e748806413a16, 57),
+    (0x51a7ce5a70af0350, 58),
+    (0x1336a2cac7de02a6, 58),
+    (0xd792b52f2839c0c8, 58),
+    (0xf21c71b123a1b66d, 58),
+    (0x8c0ca9e3b6a9b500, 58),
+    (0x07b0565cfe6882c2, 58),
+    (0xf55aee7bdba82cde, 57),
+    (0xef839a7592f0471c, 58),
+    (0x2d2317652d0e3114, 58),
+    (0xba48f31dbe74b3d0, 58),
+    (0x34d8b1023570b992, 58),
+    (0x1512a54e1a176fcf, 58),
+    (0x743ee1bb912d3b39, 58),
+    (0xe622f9da6108bfb8, 58),
+    (0x08a834aeca2d6c9e, 58),
+    (0xb3fdbc31ac0f2444, 58),
+    (0x06defaaef3311041, 58),
+    (0x33769d177dac2418, 56),
+    (0x314f3ba9f39f3933, 56),
+    (0x625acb25680b5858, 56),
+    (0x0556ca8a6690b9d0, 56),
+    (0xc46247411acfe3d6, 58),
+    (0xcce19b3ca20cf703, 58),
+    (0x73634331786a275e, 58),
+    (0x8d56cbfd2b979569, 58),
+    (0xade013c1b376ca7b, 56),
+    (0xdf8224c07680a210, 54),
+    (0x3353807dce1dffde, 54),
+    (0xa7aa737bb643fc68, 56),
+    (0xd742de5959d5b684, 58),
+    (0x856f29c0174900aa, 58),
+    (0xa4c73d88b1276393, 58),
+    (0xa9098ab6a1ca9bfe, 58),
+    (0x99f20041003dc6a2, 56),
+    (0xf5bec7d62cd3a72c, 54),
+    (0x473e236e783c91e7, 54),
+    (0x75c05f0673dc71cf, 56),
+    (0xfbcfe490f5838424, 58),
+    (0xa29fc33cdee498bb, 58),
+    (0xcd3372469e3ee9ec, 58),
+    (0xac60cb0529a06b50, 58),
+    (0x9072ced17bc52f11, 56),
+    (0x1c5a7a203f031193, 56),
+    (0x747bdcba17a0e0ee, 56),
+    (0x28b22249c950d750, 56),
+    (0x0c72ea29a5ea29ea, 58),
+    (0x34892aa0a35b28c5, 58),
+    (0x4661b4bc9e873a59, 58),
+    (0x9b554c9ece2e2ed5, 58),
+    (0x8464b1999505eae7, 58),
+    (0x50b113e31ac6a397, 58),
+    (0x4c1b0cc2045e5fad, 58),
+    (0xf4c35fa4da0b25e0, 58),
+    (0x5504a8f0c241a1c1, 58),
+    (0xf7f2ca26f5c254a9, 58),
+    (0x48ec475212f99171, 57),
+    (0xf0b17901b3b02797, 58),
+    (0x27302235b0ebb91d, 58),
+    (0x9fcac81925024aab, 58),
+    (0xf3b8a2130e513e2c, 58),
+    (0x658be9142ea71eb9, 58),
+    (0x4baf7093af1e8078, 58),
+    (0x712312e23233cc38, 57),
+];
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::game::board::square::*;
 
     #[test]
+    #[cfg(feature = "magic-search")]
     fn test_find_valid_magic_num() {
         let mask = create_rook_mask(E1);
         let o = occupancies_from_mask(mask);
         find_valid_magic_number(mask, 2_usize.pow(mask.0.count_ones() + H), &o);
     }
+
+    /// prints freshly searched magic numbers in the format of ROOK_MAGICS and BISHOP_MAGICS
+    #[test]
+    #[ignore]
+    #[cfg(feature = "magic-search")]
+    fn print_magic_constants() {
+        for (name, create) in [
+            ("ROOK_MAGICS", AttackMagic::create_attack_magic_rook as fn(Square) -> AttackMagic),
+            ("BISHOP_MAGICS", AttackMagic::create_attack_magic_bishop),
+        ] {
+            println!("{name}:");
+            for square in Square::all() {
+                let magic = create(square);
+                println!("    (0x{:016x}, {}),", magic.magic_number, magic.shift);
+            }
+        }
+    }
+
+    /// checks every occupancy of every square against the slow attack pattern computation
+    fn assert_lookup_matches_patterns(create: impl Fn(Square) -> AttackMagic, pattern: impl Fn(Square, Occupancy) -> BoardMask) {
+        for square in Square::all() {
+            let magic = create(square);
+            for occ in occupancies_from_mask(magic.mask) {
+                assert_eq!(
+                    magic.attack_patterns[magic.index(occ)],
+                    pattern(square, occ),
+                    "wrong pattern on {square}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_magic_lookup_matches_patterns() {
+        let rook = |sq: Square| AttackMagic::from_const_rook(sq, ROOK_MAGICS[sq.as_index()].0, ROOK_MAGICS[sq.as_index()].1);
+        let bishop = |sq: Square| AttackMagic::from_const_bishop(sq, BISHOP_MAGICS[sq.as_index()].0, BISHOP_MAGICS[sq.as_index()].1);
+        assert_lookup_matches_patterns(rook, create_rook_attack_pattern);
+        assert_lookup_matches_patterns(bishop, create_bishop_attack_pattern);
+        // whichever backend the tables use
+        assert_lookup_matches_patterns(AttackMagic::rook, create_rook_attack_pattern);
+        assert_lookup_matches_patterns(AttackMagic::bishop, create_bishop_attack_pattern);
+    }
+
+    #[test]
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    fn test_pext_lookup_matches_patterns() {
+        if !std::arch::is_x86_feature_detected!("bmi2") {
+            return;
+        }
+        let rook = |sq| AttackMagic::from_pext(create_rook_mask(sq), |occ| create_rook_attack_pattern(sq, occ)).unwrap();
+        let bishop = |sq| AttackMagic::from_pext(create_bishop_mask(sq), |occ| create_bishop_attack_pattern(sq, occ)).unwrap();
+        assert_lookup_matches_patterns(rook, create_rook_attack_pattern);
+        assert_lookup_matches_patterns(bishop, create_bishop_attack_pattern);
+    }
+
+    #[test]
+    fn test_const_magics_are_collision_free() {
+        for i in 0..64 {
+            let square = Square::new(i).unwrap();
+            for (mask, (magic_number, shift)) in [
+                (create_rook_mask(square), ROOK_MAGICS[i as usize]),
+                (create_bishop_mask(square), BISHOP_MAGICS[i as usize]),
+            ] {
+                let mut seen = vec![false; 1 << (64 - shift)];
+                for occ in occupancies_from_mask(mask) {
+                    let h = occ.hash(mask, magic_number, shift);
+                    assert!(!seen[h], "collision for {square}");
+                    seen[h] = true;
+                }
+            }
+        }
+    }
 }
@@ -3,43 +3,35 @@ use crate::game::board::Occupancy;
 use crate::game::board::square::Square;
 
 /// the knight is no sliding piece, so we don't need to consider occupancy patterns for the knights
-/// movement. Therefore the logic is fairly straight forward
+/// movement. We only need to make sure the jumps don't wrap around the edges of the board.
 pub fn create_knight_attack_pattern(square: Square) -> BoardMask {
     let mut pattern = BoardMask(0);
-    // -2 -1
-    if square > Square::new(17).unwrap() {
-        pattern.add_square(Square::new(square.as_u8() - 17).unwrap());
-    }
-    // -2 + 1
-    if square > Square::new(17).unwrap() {
-        pattern.add_square(Square::new(square.as_u8() - 15).unwrap());
-    }
-    // -1, -2
-    if square > Square::new(17).unwrap() {
-        pattern.add_square(Square::new(square.as_u8() - 10).unwrap());
-    }
-    // -1, +2
-    if square > Square::new(17).unwrap() {
-        pattern.add_square(Square::new(square.as_u8() - 6).unwrap());
+    for (delta_rank, delta_file) in [(-2, -1), (-2, 1), (-1, -2), (-1, 2), (1, -2), (1, 2), (2, -1), (2, 1)] {
+        // move_on_file changes the rank and move_on_rank changes the file, both fail if we
+        // would leave the board
+        if let Ok(s) = square.move_on_file(delta_rank).and_then(|s| s.move_on_rank(delta_file)) {
+            pattern.add_square(s);
+        }
     }
+    pattern
+}
 
-    // +1 -2
-    if square < Square::new(58).unwrap() {
-        pattern.add_square(Square::new(square.as_u8() + 6).unwrap());
-    }
-    // +1 +2
-    if square < Square::new(54).unwrap() {
-        pattern.add_square(Square::new(square.as_u8() + 10).unwrap());
-    }
-    // +2 -1
-    if square < Square::new(49).unwrap() {
-        pattern.add_square(Square::new(square.as_u8() + 15).unwrap());
-    }
-    // +2 +1
-    if square < Square::new(47).unwrap() {
-        pattern.add_square(Square::new(square.as_u8() + 17).unwrap());
+/// the king is no sliding piece either, it can step onto any of the 8 adjacent squares. We only
+/// need to make sure that we don't wrap around the a and h files or fall off the board.
+pub fn create_king_attack_pattern(square: Square) -> BoardMask {
+    let mut pattern = BoardMask(0);
+    for delta_rank in -1..=1 {
+        for delta_file in -1..=1 {
+            if delta_rank == 0 && delta_file == 0 {
+                continue;
+            }
+            // move_on_file changes the rank and move_on_rank changes the file, both fail if we
+            // would leave the board
+            if let Ok(s) = square.move_on_file(delta_rank).and_then(|s| s.move_on_rank(delta_file)) {
+                pattern.add_square(s);
+            }
+        }
     }
-
     pattern
 }
 
@@ -207,13 +199,7 @@ mod test {
     use crate::game::board::square::*;
 
     fn squares_from_mask(mask: BoardMask) -> Vec<String> {
-        let mut squares = Vec::new();
-        for i in 0..64 {
-            if mask.contains(Square::new(i).unwrap()) {
-                squares.push(Square::new(i).unwrap().to_string());
-            }
-        }
-        squares
+        Square::all().filter(|&sq| mask.contains(sq)).map(|sq| sq.to_string()).collect()
     }
 
     fn check_bit_board_pattern(expected: BoardMask, computed: BoardMask) {
@@ -228,6 +214,75 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_create_knight_attack_pattern() {
+        let expected = BoardMask(0)
+            .with_square(C3)
+            .with_square(C5)
+            .with_square(D2)
+            .with_square(D6)
+            .with_square(F2)
+            .with_square(F6)
+            .with_square(G3)
+            .with_square(G5);
+        check_bit_board_pattern(expected, create_knight_attack_pattern(E4));
+    }
+
+    #[test]
+    fn test_create_knight_attack_pattern_edges() {
+        check_bit_board_pattern(BoardMask(0).with_square(B3).with_square(C2), create_knight_attack_pattern(A1));
+        check_bit_board_pattern(BoardMask(0).with_square(G3).with_square(F2), create_knight_attack_pattern(H1));
+        check_bit_board_pattern(BoardMask(0).with_square(B6).with_square(C7), create_knight_attack_pattern(A8));
+        check_bit_board_pattern(BoardMask(0).with_square(G6).with_square(F7), create_knight_attack_pattern(H8));
+        check_bit_board_pattern(
+            BoardMask(0).with_square(A4).with_square(C4).with_square(D3).with_square(D1),
+            create_knight_attack_pattern(B2),
+        );
+        // a knight on the a file must not jump over to the g and h files
+        check_bit_board_pattern(
+            BoardMask(0).with_square(B2).with_square(C3).with_square(C5).with_square(B6),
+            create_knight_attack_pattern(A4),
+        );
+    }
+
+    #[test]
+    fn test_create_king_attack_pattern() {
+        let expected = BoardMask(0)
+            .with_square(D3)
+            .with_square(D4)
+            .with_square(D5)
+            .with_square(E3)
+            .with_square(E5)
+            .with_square(F3)
+            .with_square(F4)
+            .with_square(F5);
+        check_bit_board_pattern(expected, create_king_attack_pattern(E4));
+    }
+
+    #[test]
+    fn test_create_king_attack_pattern_edges() {
+        // a king on the a file must not wrap around to the h file
+        let expected = BoardMask(0)
+            .with_square(A3)
+            .with_square(A5)
+            .with_square(B3)
+            .with_square(B4)
+            .with_square(B5);
+        check_bit_board_pattern(expected, create_king_attack_pattern(A4));
+
+        // and a king on the h file must not wrap around to the a file
+        let expected = BoardMask(0)
+            .with_square(H3)
+            .with_square(H5)
+            .with_square(G3)
+            .with_square(G4)
+            .with_square(G5);
+        check_bit_board_pattern(expected, create_king_attack_pattern(H4));
+
+        assert_eq!(create_king_attack_pattern(A1).count_ones(), 3);
+        assert_eq!(create_king_attack_pattern(H8).count_ones(), 3);
+    }
+
     #[test]
     fn test_create_bishop_mask() {
         // put the bishop on d3;
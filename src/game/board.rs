@@ -5,6 +5,12 @@ use piece::{Color, Piece};
 use square::*;
 
 use crate::game::ATTACK_TABLES;
+use crate::game::attack_tables::move_logic::{create_bishop_attack_pattern, create_rook_attack_pattern};
+use crate::game::chess_move::Move;
+use crate::game::error::PositionError;
+use crate::game::evaluation::piece_score;
+use crate::game::mask::{BitIter, BoardMask};
+use crate::game::zobrist::ZOBRIST_KEYS;
 
 /// a representation of the board where each bit in the u64 represents the square on the board and
 /// whether it is occupied. This makes checking for blocking pieces as easy as applying a mask to
@@ -25,7 +31,6 @@ impl Occupancy {
     pub fn remove_square(&mut self, square: Square) {
         self.0 &= !(1_u64 << square.as_u8());
     }
-    #[allow(unused)]
     /// returns occupancy with the given square marked as free
     pub fn with_square_removed(&self, square: Square) -> Self {
         Occupancy(self.0 & !(1_u64 << square.as_u8()))
@@ -34,16 +39,40 @@ impl Occupancy {
     pub fn is_occupied(&self, square: Square) -> bool {
         self.0 & 1_u64 << square.as_u8() != 0
     }
+    /// iterates over the occupied squares, ordered from a1 to h8
+    pub fn iter(&self) -> BitIter {
+        BoardMask(self.0).iter()
+    }
+    /// the number of occupied squares
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
 }
 
 /// represents the current Board state.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BitBoard {
     pub board: [Option<(Piece, Color)>; 64],
 
     // tracks whether each square is occupied, must be kept in sync with the board. Should only be
     // used for lookups in the Attack Tables.
     pub occupancy: Occupancy,
+
+    // the squares occupied by each color, kept in sync with the board like occupancy
+    white_occupancy: Occupancy,
+    black_occupancy: Occupancy,
+
+    // the squares of each kind of piece for both colors, indexed by piece_index. Derived from the
+    // board, which stays the source of truth.
+    piece_masks: [BoardMask; 12],
+
+    // the zobrist hash of the pieces on the board, updated with every piece placed or removed
+    hash: u64,
+
+    // the material and piece square scores of white minus black with the default evaluation,
+    // updated like the hash so the search doesn't have to add up every piece
+    material: i32,
+    placement: i32,
 }
 
 impl Default for BitBoard {
@@ -51,11 +80,158 @@ impl Default for BitBoard {
         Self {
             board: [None; 64],
             occupancy: Occupancy(0),
+            white_occupancy: Occupancy(0),
+            black_occupancy: Occupancy(0),
+            piece_masks: [BoardMask(0); 12],
+            hash: 0,
+            material: 0,
+            placement: 0,
         }
     }
 }
 
+/// only the pieces are stored, the occupancies, masks and the hash are derived from them again when
+/// loading, so they can't get out of sync
+#[cfg(feature = "serde")]
+impl serde::Serialize for BitBoard {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.board.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BitBoard {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pieces = Vec::<Option<(Piece, Color)>>::deserialize(deserializer)?;
+        if pieces.len() != 64 {
+            return Err(serde::de::Error::invalid_length(
+                pieces.len(),
+                &"one entry for each of the 64 squares",
+            ));
+        }
+        let mut board = Self::empty();
+        for (square, piece) in Square::all().zip(pieces) {
+            if let Some((piece, color)) = piece {
+                board.place_piece_on_square(piece, color, square);
+            }
+        }
+        Ok(board)
+    }
+}
+
+// the squares of the a and the h file
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_H: u64 = FILE_A << 7;
+// the squares of the ranks the pawns start on
+const RANK_2: u64 = 0xff << 8;
+const RANK_7: u64 = 0xff << 48;
+
+/// the squares the pawns of color can push to by one square
+pub fn pawn_single_pushes(pawns: BoardMask, empty: BoardMask, color: Color) -> BoardMask {
+    BoardMask(if color.is_white() { pawns.0 << 8 } else { pawns.0 >> 8 }) & empty
+}
+
+/// the squares the pawns of color still on their starting rank can push to by two squares, which
+/// needs both squares in front of them to be empty
+pub fn pawn_double_pushes(pawns: BoardMask, empty: BoardMask, color: Color) -> BoardMask {
+    let start = BoardMask(if color.is_white() { RANK_2 } else { RANK_7 });
+    pawn_single_pushes(pawn_single_pushes(pawns & start, empty, color), empty, color)
+}
+
+/// the squares attacked by the pawns of color. The file masks keep pawns on the edge from
+/// attacking onto the other side of the board.
+pub fn pawn_attacks(pawns: BoardMask, color: Color) -> BoardMask {
+    let pawns = pawns.0;
+    BoardMask(if color.is_white() {
+        (pawns & !FILE_A) << 7 | (pawns & !FILE_H) << 9
+    } else {
+        (pawns & !FILE_A) >> 9 | (pawns & !FILE_H) >> 7
+    })
+}
+
+/// the index of the mask for the piece and color in BitBoard::piece_masks
+fn piece_index(piece: Piece, color: Color) -> usize {
+    let kind = match piece {
+        Piece::Pawn => 0,
+        Piece::Knight => 1,
+        Piece::Bishop => 2,
+        Piece::Rook { .. } => 3,
+        Piece::Queen => 4,
+        Piece::King { .. } => 5,
+    };
+    kind * 2 + color as usize
+}
+
 impl BitBoard {
+    /// a board without any pieces on it
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// builds the board from the pieces on every square, a1 first, in one pass instead of placing
+    /// them one by one. Fails if the position can't come up in a game with turn to move, see
+    /// validate.
+    pub fn from_array(squares: [Option<(Piece, Color)>; 64], turn: Color) -> Result<Self, PositionError> {
+        let mut bb = Self {
+            board: squares,
+            ..Self::default()
+        };
+        for (square, (piece, color)) in Square::all().zip(squares).filter_map(|(square, p)| p.map(|p| (square, p))) {
+            bb.occupancy.add_square(square);
+            bb.color_occupancy_mut(color).add_square(square);
+            bb.piece_masks[piece_index(piece, color)].add_square(square);
+            bb.hash ^= ZOBRIST_KEYS.piece(piece, color, square);
+            bb.add_score(piece, color, square, 1);
+        }
+        bb.validate(turn)?;
+        Ok(bb)
+    }
+
+    /// checks that the position could come up in a game with turn to move: each side has exactly one
+    /// king, the kings aren't next to each other, no pawn stands on the first or last rank and the
+    /// side that just moved isn't left in check.
+    pub fn validate(&self, turn: Color) -> Result<(), PositionError> {
+        for color in [Color::White, Color::Black] {
+            let count = self.count(Piece::King { has_moved: true }, color);
+            if count != 1 {
+                return Err(PositionError::KingCount { color, count });
+            }
+        }
+        let (white, black) = (
+            self.king_square(Color::White).expect("counted one king"),
+            self.king_square(Color::Black).expect("counted one king"),
+        );
+        if white.chebyshev_distance(black) == 1 {
+            return Err(PositionError::AdjacentKings { white, black });
+        }
+        for color in [Color::White, Color::Black] {
+            if let Some(square) = self.pieces(Piece::Pawn, color).iter().find(|square| square.get_rank().is_on_edge()) {
+                return Err(PositionError::PawnOnBackRank { square });
+            }
+        }
+        let waiting = !turn;
+        let king = if waiting.is_white() { white } else { black };
+        if self.square_is_controlled_by(king, turn) {
+            return Err(PositionError::OpponentInCheck { color: waiting });
+        }
+        Ok(())
+    }
+
+    /// the board flipped upside down with the colors of the pieces swapped, so the position is the
+    /// same one seen from the other side
+    pub fn mirror(&self) -> Self {
+        let mut mirrored = Self::empty();
+        for (square, &(piece, color)) in Square::all()
+            .zip(&self.board)
+            .filter_map(|(square, p)| p.as_ref().map(|p| (square, p)))
+        {
+            // flipping the rank of an index only changes its upper three bits
+            let flipped = Square::new(square.as_u8() ^ 56).expect("a flipped square is still on the board");
+            mirrored.place_piece_on_square(piece, !color, flipped);
+        }
+        mirrored
+    }
+
     pub fn init() -> Self {
         let mut bb = Self::default();
         bb.setup_for_game();
@@ -65,60 +241,74 @@ impl BitBoard {
     /// returns true if the square is under attack by a piece from the given color. Note that this
     /// function does not check for pins.
     pub fn square_is_controlled_by(&self, square: Square, color: Color) -> bool {
-        let rook_pattern = ATTACK_TABLES.get_attack_pattern_rook(square, self.occupancy);
-        let knight_pattern = ATTACK_TABLES.get_attack_pattern_knight(square);
-        let bishop_pattern = ATTACK_TABLES.get_attack_pattern_bishop(square, self.occupancy);
-        // checks for major pieces
-        if self.board.iter().enumerate().any(|(i, p)| {
-            p.is_some_and(|(piece, col)| {
-                let s = Square::try_from(i).unwrap();
-                col == color
-                    && ((rook_pattern.contains(s) && (piece.is_rook() || piece.is_queen()))
-                        || (bishop_pattern.contains(s) && piece.is_bishop() || piece.is_queen())
-                        || (knight_pattern.contains(s) && piece.is_knight()))
-            })
-        }) {
-            return true;
-        }
-        // checks for king
-        if square.move_on_rank(1).is_ok_and(|s| {
-            self.board[s.as_index()].is_some_and(|(piece, col)| piece.is_king() && col == color)
-                || s.move_on_file(1).is_ok_and(|sf| {
-                    self.board[sf.as_index()]
-                        .is_some_and(|(piece, col)| col == color && ((piece.is_king()) || (piece.is_pawn() && color == Color::Black)))
-                })
-        }) {
-            return true;
-        }
-        if square.move_on_rank(-1).is_ok_and(|s| {
-            self.board[s.as_index()].is_some_and(|(piece, col)| piece.is_king() && col == color)
-                || s.move_on_file(-1).is_ok_and(|sf| {
-                    self.board[sf.as_index()]
-                        .is_some_and(|(piece, col)| col == color && ((piece.is_king()) || (piece.is_pawn() && color == Color::White)))
-                })
-        }) {
-            return true;
-        }
-        if square.move_on_rank(1).is_ok_and(|s| {
-            self.board[s.as_index()].is_some_and(|(piece, col)| piece.is_king() && col == color)
-                || s.move_on_file(-1).is_ok_and(|sf| {
-                    self.board[sf.as_index()]
-                        .is_some_and(|(piece, col)| col == color && ((piece.is_king()) || (piece.is_pawn() && color == Color::Black)))
-                })
-        }) {
-            return true;
-        }
-        if square.move_on_rank(-1).is_ok_and(|s| {
-            self.board[s.as_index()].is_some_and(|(piece, col)| piece.is_king() && col == color)
-                || s.move_on_file(-1).is_ok_and(|sf| {
-                    self.board[sf.as_index()]
-                        .is_some_and(|(piece, col)| col == color && ((piece.is_king()) || (piece.is_pawn() && color == Color::White)))
-                })
-        }) {
-            return true;
-        }
-
-        false
+        self.attackers_through(square, color, self.occupancy).0 != 0
+    }
+
+    /// the squares of the pieces of color attacking square, with the sliding pieces only blocked
+    /// by the pieces in occupancy. Like square_is_controlled_by this ignores pins.
+    pub fn attackers_through(&self, square: Square, color: Color, occupancy: Occupancy) -> BoardMask {
+        let queens = self.pieces(Piece::Queen, color);
+        let rooks = self.pieces(Piece::Rook { has_moved: true }, color) | queens;
+        let bishops = self.pieces(Piece::Bishop, color) | queens;
+        // the patterns are symmetric, so a piece standing on the pattern of its kind attacks the
+        // square. A pawn of color attacking the square stands where a pawn of the other color on
+        // the square would attack.
+        (ATTACK_TABLES.get_attack_pattern_rook(square, occupancy) & rooks)
+            | (ATTACK_TABLES.get_attack_pattern_bishop(square, occupancy) & bishops)
+            | (ATTACK_TABLES.get_attack_pattern_knight(square) & self.pieces(Piece::Knight, color))
+            | (ATTACK_TABLES.get_attack_pattern_king(square) & self.pieces(Piece::King { has_moved: true }, color))
+            | (pawn_attacks(BoardMask::default().with_square(square), !color) & self.pieces(Piece::Pawn, color))
+    }
+
+    /// returns all squares attacked by the pieces of color, including the squares of the pieces they
+    /// defend. Like square_is_controlled_by this ignores pins.
+    pub fn attacks_by(&self, color: Color) -> BoardMask {
+        self.attacks_through(color, self.occupancy)
+    }
+
+    /// like attacks_by, but the sliding pieces are only blocked by the pieces in occupancy. Used to
+    /// see through the king, which can't hide from a slider by stepping back along its line.
+    pub fn attacks_through(&self, color: Color, occupancy: Occupancy) -> BoardMask {
+        let mut attacks = pawn_attacks(self.pieces(Piece::Pawn, color), color);
+        let queens = self.pieces(Piece::Queen, color);
+        for square in self.pieces(Piece::Knight, color).iter() {
+            attacks |= ATTACK_TABLES.get_attack_pattern_knight(square);
+        }
+        for square in self.pieces(Piece::King { has_moved: true }, color).iter() {
+            attacks |= ATTACK_TABLES.get_attack_pattern_king(square);
+        }
+        for square in (self.pieces(Piece::Rook { has_moved: true }, color) | queens).iter() {
+            attacks |= ATTACK_TABLES.get_attack_pattern_rook(square, occupancy);
+        }
+        for square in (self.pieces(Piece::Bishop, color) | queens).iter() {
+            attacks |= ATTACK_TABLES.get_attack_pattern_bishop(square, occupancy);
+        }
+        attacks
+    }
+
+    /// the number of pieces of the given kind and color on the board
+    pub fn count(&self, piece: Piece, color: Color) -> u32 {
+        self.pieces(piece, color).count_ones()
+    }
+
+    /// the squares a rook, bishop or queen on square attacks, found by walking along the rays
+    /// instead of looking them up in the magic tables. It is slow, but doesn't depend on the magic
+    /// numbers, so it can validate them. The mask is empty for pieces that don't slide.
+    pub fn slider_attacks_classical(square: Square, piece: Piece, occupancy: Occupancy) -> BoardMask {
+        // the rays stop at the first occupied square, which must not be the slider itself
+        let occupancy = occupancy.with_square_removed(square);
+        match piece {
+            Piece::Rook { .. } => create_rook_attack_pattern(square, occupancy),
+            Piece::Bishop => create_bishop_attack_pattern(square, occupancy),
+            Piece::Queen => create_rook_attack_pattern(square, occupancy) | create_bishop_attack_pattern(square, occupancy),
+            _ => BoardMask::default(),
+        }
+    }
+
+    /// returns the squares of all pieces of the given kind and color. The has_moved flag of kings
+    /// and rooks is ignored.
+    pub fn pieces(&self, piece: Piece, color: Color) -> BoardMask {
+        self.piece_masks[piece_index(piece, color)]
     }
 
     fn setup_for_game(&mut self) {
@@ -160,32 +350,419 @@ impl BitBoard {
     }
 
     pub fn place_piece_on_square(&mut self, piece: Piece, color: Color, square: Square) -> Option<(Piece, Color)> {
+        let replaced = self.remove_piece_from_square(square);
         self.occupancy.add_square(square);
-        self.board[square.as_index()].replace((piece, color))
+        self.color_occupancy_mut(color).add_square(square);
+        self.piece_masks[piece_index(piece, color)].add_square(square);
+        self.hash ^= ZOBRIST_KEYS.piece(piece, color, square);
+        self.add_score(piece, color, square, 1);
+        self.board[square.as_index()] = Some((piece, color));
+        replaced
     }
 
     pub fn remove_piece_from_square(&mut self, square: Square) -> Option<(Piece, Color)> {
         self.occupancy.remove_square(square);
-        self.board[square.as_index()].take()
+        let removed = self.board[square.as_index()].take();
+        if let Some((piece, color)) = removed {
+            self.color_occupancy_mut(color).remove_square(square);
+            self.piece_masks[piece_index(piece, color)].remove_square(square);
+            self.hash ^= ZOBRIST_KEYS.piece(piece, color, square);
+            self.add_score(piece, color, square, -1);
+        }
+        removed
     }
 
-    pub fn get_piece_on_square(&self, square: Square) -> Option<&(Piece, Color)> {
-        self.board[square.as_index()].as_ref()
+    /// returns the squares occupied by pieces of the given color
+    pub fn occupancy_for(&self, color: Color) -> Occupancy {
+        match color {
+            Color::White => self.white_occupancy,
+            Color::Black => self.black_occupancy,
+        }
     }
 
-    pub fn get_piece_on_square_mut(&mut self, square: Square) -> Option<&mut (Piece, Color)> {
-        self.board[square.as_index()].as_mut()
+    fn color_occupancy_mut(&mut self, color: Color) -> &mut Occupancy {
+        match color {
+            Color::White => &mut self.white_occupancy,
+            Color::Black => &mut self.black_occupancy,
+        }
+    }
+
+    pub fn get_piece_on_square(&self, square: Square) -> Option<&(Piece, Color)> {
+        self.board[square.as_index()].as_ref()
     }
 
     pub fn is_occupied(&self, square: Square) -> bool {
         self.occupancy.is_occupied(square)
     }
+
+    /// the zobrist hash of all pieces on the board, see Game::zobrist_hash
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// the material of white minus the material of black in centipawns, see evaluation::piece_value
+    pub fn material(&self) -> i32 {
+        self.material
+    }
+
+    /// the piece square bonuses of white minus the ones of black with the default tables
+    pub fn placement(&self) -> i32 {
+        self.placement
+    }
+
+    // adds (sign 1) or takes away (sign -1) the score of a piece placed on or removed from square
+    fn add_score(&mut self, piece: Piece, color: Color, square: Square, sign: i32) {
+        let (material, placement) = piece_score(piece, color, square);
+        self.material += sign * material;
+        self.placement += sign * placement;
+    }
+
+    /// returns the square of the king of the given color, if there is one on the board
+    pub fn king_square(&self, color: Color) -> Option<Square> {
+        let kings = self.pieces(Piece::King { has_moved: true }, color);
+        // an empty mask has 64 trailing zeros, which isn't a valid square
+        Square::new(kings.0.trailing_zeros() as u8).ok()
+    }
+
+    /// moves the piece of the move on the board without checking whether the move is valid. This
+    /// also moves the rook when castling, removes the pawn taken en passant and promotes pawns. Returns everything
+    /// needed to take the move back with unmake_move.
+    pub fn make_move(&mut self, mv: &Move) -> MoveUndo {
+        let from = mv.get_from();
+        let to = mv.get_to();
+        let delta_file = from.get_delta_file(to);
+
+        let mut undo = MoveUndo {
+            moved: self.get_piece_on_square(from).copied(),
+            taken: None,
+            rook: None,
+        };
+
+        if mv.get_piece().is_king() && delta_file.abs() == 2 {
+            // castling, the rook ends up on the square the king passed
+            let rook_file = if delta_file > 0 { File::H } else { File::A };
+            let rook_sq = Square::from_rank_file(from.get_rank(), rook_file);
+            if let Some((rook, col)) = self.remove_piece_from_square(rook_sq) {
+                let rook_to = from.move_on_rank(delta_file / 2).unwrap();
+                let mut moved_rook = rook;
+                moved_rook.make_moved();
+                self.place_piece_on_square(moved_rook, col, rook_to);
+                undo.rook = Some(((rook, col), rook_sq, rook_to));
+            }
+        } else if mv.get_piece().is_pawn() && delta_file != 0 && !self.is_occupied(to) {
+            // en passant, the taken pawn stands next to us on the file we move to
+            let taken_sq = Square::from_rank_file(from.get_rank(), to.get_file());
+            undo.taken = self.remove_piece_from_square(taken_sq).map(|taken| (taken, taken_sq));
+        }
+
+        if let Some((mut piece, col)) = self.remove_piece_from_square(from) {
+            // only a pawn promotes, even if a broken move asks another piece to
+            if piece.is_pawn()
+                && let Some(promotion) = mv.get_promotion()
+            {
+                piece = promotion;
+            }
+            piece.make_moved();
+            if let Some(taken) = self.place_piece_on_square(piece, col, to) {
+                undo.taken = Some((taken, to));
+            }
+        }
+        undo
+    }
+
+    /// takes back a move that was applied with make_move, restoring the taken piece, promoted
+    /// pawns and the original has_moved flags.
+    pub fn unmake_move(&mut self, mv: &Move, undo: &MoveUndo) {
+        self.remove_piece_from_square(mv.get_to());
+        if let Some((piece, col)) = undo.moved {
+            self.place_piece_on_square(piece, col, mv.get_from());
+        }
+        if let Some(((piece, col), square)) = undo.taken {
+            self.place_piece_on_square(piece, col, square);
+        }
+        if let Some(((rook, col), from, to)) = undo.rook {
+            self.remove_piece_from_square(to);
+            self.place_piece_on_square(rook, col, from);
+        }
+    }
+}
+
+impl BitBoard {
+    /// draws the board with rank 8 at the top, white pieces as upper case and black pieces as lower
+    /// case letters, or as chess glyphs if unicode is set. Empty squares are drawn as dots.
+    pub fn render(&self, unicode: bool) -> String {
+        let mut out = String::new();
+        for rank in Rank::all().rev() {
+            out.push_str(&format!("{}", rank as u8 + 1));
+            for square in Square::iter_rank(rank) {
+                out.push(' ');
+                out.push(
+                    self.get_piece_on_square(square)
+                        .map_or('.', |&(piece, color)| piece_char(piece, color, unicode)),
+                );
+            }
+            out.push('\n');
+        }
+        out.push_str("  a b c d e f g h\n");
+        out
+    }
+}
+
+impl std::fmt::Display for BitBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+/// the character drawn for a piece by BitBoard::render
+fn piece_char(piece: Piece, color: Color, unicode: bool) -> char {
+    if !unicode {
+        return piece.to_fen_char(color);
+    }
+    let (white, black) = match piece {
+        Piece::Pawn => ('♙', '♟'),
+        Piece::Knight => ('♘', '♞'),
+        Piece::Bishop => ('♗', '♝'),
+        Piece::Rook { .. } => ('♖', '♜'),
+        Piece::Queen => ('♕', '♛'),
+        Piece::King { .. } => ('♔', '♚'),
+    };
+    if color.is_white() { white } else { black }
+}
+
+/// everything needed to take back a move on the board, which can't be derived from the move
+/// itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveUndo {
+    /// the moved piece as it was before the move, so the has_moved flag can be restored
+    pub moved: Option<(Piece, Color)>,
+    /// the taken piece and the square it was taken on, which differs from the target square when
+    /// taking en passant
+    pub taken: Option<((Piece, Color), Square)>,
+    /// the rook moved when castling as it was before the move, with its starting and target square
+    pub rook: Option<((Piece, Color), Square, Square)>,
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_occupancy_iter_and_count() {
+        let bb = BitBoard::init();
+        let squares: Vec<Square> = bb.occupancy.iter().collect();
+        assert_eq!(squares.len(), 32);
+        assert_eq!(bb.occupancy.count(), 32);
+        assert_eq!(squares.first(), Some(&A1));
+        assert_eq!(squares.last(), Some(&H8));
+        assert!(squares.iter().all(|square| bb.get_piece_on_square(*square).is_some()));
+        assert_eq!(bb.occupancy_for(Color::Black).iter().count(), 16);
+        assert_eq!(Occupancy::default().iter().next(), None);
+    }
+
+    /// checks that every piece mask contains exactly the squares the board has that piece on
+    fn assert_piece_masks_in_sync(bb: &BitBoard) {
+        for (i, mask) in bb.piece_masks.iter().enumerate() {
+            for sq in Square::all() {
+                let on_board = bb.board[sq.as_index()].is_some_and(|(piece, color)| piece_index(piece, color) == i);
+                assert_eq!(mask.contains(sq), on_board, "mask {i} out of sync on {sq}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_mirror() {
+        let mut board = BitBoard::init();
+        board.remove_piece_from_square(G1);
+        board.place_piece_on_square(Piece::Knight, Color::White, F3);
+        let mirrored = board.mirror();
+        assert_eq!(mirrored.get_piece_on_square(F6), Some(&(Piece::Knight, Color::Black)));
+        assert_eq!(mirrored.get_piece_on_square(G8), None);
+        assert_eq!(
+            mirrored.get_piece_on_square(E1),
+            Some(&(Piece::King { has_moved: false }, Color::White))
+        );
+        assert_eq!(mirrored.mirror(), board);
+    }
+
+    #[test]
+    fn test_from_array() {
+        let start = BitBoard::init();
+        let bb = BitBoard::from_array(start.board, Color::White).unwrap();
+        assert_eq!(bb, start);
+        assert_eq!(bb.hash(), start.hash());
+
+        let mut squares = start.board;
+        squares[D1.as_index()] = Some((Piece::King { has_moved: true }, Color::White));
+        assert_eq!(
+            BitBoard::from_array(squares, Color::White),
+            Err(PositionError::KingCount {
+                color: Color::White,
+                count: 2
+            })
+        );
+        squares[D1.as_index()] = None;
+        squares[E8.as_index()] = None;
+        assert_eq!(
+            BitBoard::from_array(squares, Color::White),
+            Err(PositionError::KingCount {
+                color: Color::Black,
+                count: 0
+            })
+        );
+
+        let mut squares = [None; 64];
+        squares[E1.as_index()] = Some((Piece::King { has_moved: true }, Color::White));
+        squares[E2.as_index()] = Some((Piece::King { has_moved: true }, Color::Black));
+        assert_eq!(
+            BitBoard::from_array(squares, Color::White),
+            Err(PositionError::AdjacentKings { white: E1, black: E2 })
+        );
+        squares[E2.as_index()] = None;
+        squares[E8.as_index()] = Some((Piece::King { has_moved: true }, Color::Black));
+        squares[A1.as_index()] = Some((Piece::Pawn, Color::White));
+        assert_eq!(
+            BitBoard::from_array(squares, Color::White),
+            Err(PositionError::PawnOnBackRank { square: A1 })
+        );
+        squares[A1.as_index()] = Some((Piece::Rook { has_moved: true }, Color::White));
+        squares[A8.as_index()] = Some((Piece::Queen, Color::White));
+        assert_eq!(
+            BitBoard::from_array(squares, Color::White),
+            Err(PositionError::OpponentInCheck { color: Color::Black })
+        );
+        assert!(BitBoard::from_array(squares, Color::Black).is_ok());
+    }
+
+    #[test]
+    fn test_color_occupancy_is_union_of_piece_masks() {
+        let mut bb = BitBoard::init();
+        bb.remove_piece_from_square(E2);
+        bb.place_piece_on_square(Piece::Knight, Color::White, E7);
+        for color in [Color::White, Color::Black] {
+            let union = bb
+                .piece_masks
+                .iter()
+                .skip(color as usize)
+                .step_by(2)
+                .fold(0, |acc, mask| acc | mask.0);
+            assert_eq!(bb.occupancy_for(color), Occupancy(union));
+        }
+        assert_eq!(bb.occupancy_for(Color::White).0 | bb.occupancy_for(Color::Black).0, bb.occupancy.0);
+        assert!(bb.occupancy_for(Color::White).is_occupied(E7));
+        assert!(!bb.occupancy_for(Color::Black).is_occupied(E7));
+    }
+
+    #[test]
+    fn test_piece_masks_stay_in_sync() {
+        let mut bb = BitBoard::init();
+        assert_piece_masks_in_sync(&bb);
+        assert_eq!(bb.pieces(Piece::Pawn, Color::White).count_ones(), 8);
+        assert_eq!(bb.pieces(Piece::Rook { has_moved: true }, Color::Black).count_ones(), 2);
+
+        // replacing a piece removes it from its mask
+        bb.place_piece_on_square(Piece::Queen, Color::White, E7);
+        assert_piece_masks_in_sync(&bb);
+        assert_eq!(bb.pieces(Piece::Pawn, Color::Black).count_ones(), 7);
+
+        // castling, en passant and promotion move more than one piece
+        bb.remove_piece_from_square(F1);
+        bb.remove_piece_from_square(G1);
+        bb.place_piece_on_square(Piece::Pawn, Color::White, E5);
+        bb.place_piece_on_square(Piece::Pawn, Color::Black, D5);
+        let moves = [
+            Move::new(Piece::King { has_moved: false }, E1, G1, None),
+            Move::new(Piece::Pawn, E5, D6, Some(Piece::Pawn)),
+            Move::promotions(E7, D8, Some(Piece::Queen))[3],
+        ];
+        for mv in moves {
+            let before = bb.clone();
+            let undo = bb.make_move(&mv);
+            assert_piece_masks_in_sync(&bb);
+            bb.unmake_move(&mv, &undo);
+            assert_eq!(bb, before);
+        }
+    }
+
+    #[test]
+    fn test_attacks_by_start_position() {
+        let bb = BitBoard::init();
+        // everything on the third rank and every piece but the rooks on the first two ranks, which
+        // are covered by another piece
+        let mut expected = BoardMask(0x00ff_ffff);
+        expected.remove_square(A1);
+        expected.remove_square(H1);
+        assert_eq!(bb.attacks_by(Color::White), expected);
+        let mut expected = BoardMask(0xffff_ff00_0000_0000);
+        expected.remove_square(A8);
+        expected.remove_square(H8);
+        assert_eq!(bb.attacks_by(Color::Black), expected);
+    }
+
+    #[test]
+    fn test_attacks_by_matches_square_is_controlled_by() {
+        let game = crate::game::Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        for color in [Color::White, Color::Black] {
+            let attacks = game.board.attacks_by(color);
+            for sq in Square::all() {
+                assert_eq!(attacks.contains(sq), game.board.square_is_controlled_by(sq, color), "{sq}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_magic_lookup_matches_classical_attacks() {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..2000 {
+            // xorshift, anding two numbers leaves about a quarter of the squares occupied
+            let mut next = || {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            };
+            let occupancy = Occupancy(next() & next());
+            for square in Square::all() {
+                for (piece, magic) in [
+                    (
+                        Piece::Rook { has_moved: true },
+                        ATTACK_TABLES.get_attack_pattern_rook(square, occupancy),
+                    ),
+                    (Piece::Bishop, ATTACK_TABLES.get_attack_pattern_bishop(square, occupancy)),
+                    (Piece::Queen, ATTACK_TABLES.get_attack_pattern_queen(square, occupancy)),
+                ] {
+                    assert_eq!(
+                        BitBoard::slider_attacks_classical(square, piece, occupancy),
+                        magic,
+                        "{piece} on {square}"
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            BitBoard::slider_attacks_classical(E4, Piece::Knight, Occupancy(0)),
+            BoardMask::default()
+        );
+    }
+
+    #[test]
+    fn test_render_start_position() {
+        let expected = "\
+8 r n b q k b n r
+7 p p p p p p p p
+6 . . . . . . . .
+5 . . . . . . . .
+4 . . . . . . . .
+3 . . . . . . . .
+2 P P P P P P P P
+1 R N B Q K B N R
+  a b c d e f g h
+";
+        assert_eq!(BitBoard::init().to_string(), expected);
+        assert!(BitBoard::init().render(true).starts_with("8 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜\n7 ♟"));
+    }
+
     #[test]
     fn test_place_piece_on_square() {
         // setup empty board
@@ -210,6 +787,70 @@ mod test {
         assert!(bb.board[28].is_none());
         assert!(!bb.occupancy.is_occupied(sq));
     }
+    #[test]
+    fn test_queen_on_blocked_diagonal_does_not_control_square() {
+        let mut bb = BitBoard::default();
+        bb.place_piece_on_square(Piece::Queen, Color::Black, A8);
+        bb.place_piece_on_square(Piece::Pawn, Color::White, C6);
+        assert!(!bb.square_is_controlled_by(E4, Color::Black));
+        // the square right in front of the blocker is still controlled
+        assert!(bb.square_is_controlled_by(B7, Color::Black));
+    }
+
+    #[test]
+    fn test_queen_controls_lines_and_diagonals() {
+        let mut bb = BitBoard::default();
+        bb.place_piece_on_square(Piece::Queen, Color::Black, D4);
+        assert!(bb.square_is_controlled_by(H8, Color::Black));
+        assert!(bb.square_is_controlled_by(D8, Color::Black));
+        assert!(bb.square_is_controlled_by(A4, Color::Black));
+        // a queen that is on neither a line nor a diagonal doesn't control the square
+        assert!(!bb.square_is_controlled_by(E6, Color::Black));
+        assert!(!bb.square_is_controlled_by(D4, Color::White));
+    }
+
+    #[test]
+    fn test_pawn_controls_diagonals() {
+        let mut bb = BitBoard::default();
+        bb.place_piece_on_square(Piece::Pawn, Color::White, E4);
+        assert!(bb.square_is_controlled_by(D5, Color::White));
+        assert!(bb.square_is_controlled_by(F5, Color::White));
+        assert!(!bb.square_is_controlled_by(E5, Color::White));
+        assert!(!bb.square_is_controlled_by(D3, Color::White));
+        assert!(!bb.square_is_controlled_by(F3, Color::White));
+        assert!(!bb.square_is_controlled_by(D5, Color::Black));
+
+        let mut bb = BitBoard::default();
+        bb.place_piece_on_square(Piece::Pawn, Color::Black, E5);
+        assert!(bb.square_is_controlled_by(D4, Color::Black));
+        assert!(bb.square_is_controlled_by(F4, Color::Black));
+        assert!(!bb.square_is_controlled_by(E4, Color::Black));
+        assert!(!bb.square_is_controlled_by(D6, Color::Black));
+    }
+
+    #[test]
+    fn test_pawn_control_does_not_wrap() {
+        let mut bb = BitBoard::default();
+        bb.place_piece_on_square(Piece::Pawn, Color::White, H3);
+        assert!(bb.square_is_controlled_by(G4, Color::White));
+        assert!(!bb.square_is_controlled_by(A5, Color::White));
+
+        let mut bb = BitBoard::default();
+        bb.place_piece_on_square(Piece::Pawn, Color::Black, A6);
+        assert!(bb.square_is_controlled_by(B5, Color::Black));
+        assert!(!bb.square_is_controlled_by(H4, Color::Black));
+    }
+
+    #[test]
+    fn test_king_controls_adjacent_squares() {
+        let mut bb = BitBoard::default();
+        bb.place_piece_on_square(Piece::King { has_moved: true }, Color::White, A1);
+        assert!(bb.square_is_controlled_by(B2, Color::White));
+        assert!(bb.square_is_controlled_by(A2, Color::White));
+        assert!(!bb.square_is_controlled_by(A3, Color::White));
+        assert!(!bb.square_is_controlled_by(H2, Color::White));
+    }
+
     #[test]
     fn test_get_piece_from_square() {
         // setup empty board
@@ -1,6 +1,7 @@
 use std::{fmt::Display, ops::Not};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Piece {
     Pawn,
     Knight,
@@ -47,6 +48,46 @@ impl Piece {
         matches!(self, Self::King { .. })
     }
 
+    /// parses a fen piece character, upper case letters are white pieces and lower case letters
+    /// black pieces. Kings and rooks are parsed as not moved yet.
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(Piece::from_fen_char('n'), Some((Piece::Knight, Color::Black)));
+    /// assert_eq!(Piece::from_fen_char('x'), None);
+    ///```
+    pub fn from_fen_char(c: char) -> Option<(Piece, Color)> {
+        let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+        let piece = match c.to_ascii_lowercase() {
+            'p' => Self::Pawn,
+            'n' => Self::Knight,
+            'b' => Self::Bishop,
+            'r' => Self::Rook { has_moved: false },
+            'q' => Self::Queen,
+            'k' => Self::King { has_moved: false },
+            _ => return None,
+        };
+        Some((piece, color))
+    }
+
+    /// the fen character of the piece, upper case for white and lower case for black. Unlike the
+    /// Display impl a pawn has a letter too.
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(Piece::Pawn.to_fen_char(Color::White), 'P');
+    /// assert_eq!(Piece::Queen.to_fen_char(Color::Black), 'q');
+    ///```
+    pub fn to_fen_char(&self, color: Color) -> char {
+        let c = match self {
+            Self::Pawn => 'p',
+            Self::Knight => 'n',
+            Self::Bishop => 'b',
+            Self::Rook { .. } => 'r',
+            Self::Queen => 'q',
+            Self::King { .. } => 'k',
+        };
+        if color.is_white() { c.to_ascii_uppercase() } else { c }
+    }
+
     pub fn make_moved(&mut self) {
         match self {
             Self::King { has_moved } => *has_moved = true,
@@ -57,6 +98,7 @@ impl Piece {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     White,
     Black,
@@ -93,3 +135,22 @@ impl Not for Color {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fen_chars_round_trip() {
+        for c in "PNBRQKpnbrqk".chars() {
+            let (piece, color) = Piece::from_fen_char(c).unwrap();
+            assert_eq!(piece.to_fen_char(color), c);
+        }
+        for c in ['x', '1', ' ', '/'] {
+            assert_eq!(Piece::from_fen_char(c), None);
+        }
+        // the has_moved flag doesn't show up in fen
+        assert_eq!(Piece::King { has_moved: true }.to_fen_char(Color::White), 'K');
+        assert_eq!(Piece::from_fen_char('R'), Some((Piece::Rook { has_moved: false }, Color::White)));
+    }
+}
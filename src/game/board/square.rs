@@ -1,5 +1,7 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
+use crate::game::board::piece::Color;
 use crate::game::error::ChessError;
 
 pub const A1: Square = Square::from_rank_file(Rank::One, File::A);
@@ -93,17 +95,122 @@ pub enum Rank {
     Eight = 7,
 }
 
+impl File {
+    /// all files from A to H
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(File::all().next(), Some(File::A));
+    /// assert_eq!(File::all().last(), Some(File::H));
+    ///```
+    pub fn all() -> impl DoubleEndedIterator<Item = File> {
+        [File::A, File::B, File::C, File::D, File::E, File::F, File::G, File::H].into_iter()
+    }
+}
+
+impl Rank {
+    /// all ranks from One to Eight
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(Rank::all().next(), Some(Rank::One));
+    /// assert_eq!(Rank::all().last(), Some(Rank::Eight));
+    ///```
+    pub fn all() -> impl DoubleEndedIterator<Item = Rank> {
+        [
+            Rank::One,
+            Rank::Two,
+            Rank::Three,
+            Rank::Four,
+            Rank::Five,
+            Rank::Six,
+            Rank::Seven,
+            Rank::Eight,
+        ]
+        .into_iter()
+    }
+
+    /// the rank the pawns of color promote on, the last one seen from their side
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(Rank::promotion_rank(Color::White), Rank::Eight);
+    /// assert_eq!(Rank::promotion_rank(Color::Black), Rank::One);
+    ///```
+    pub fn promotion_rank(color: Color) -> Rank {
+        match color {
+            Color::White => Rank::Eight,
+            Color::Black => Rank::One,
+        }
+    }
+
+    /// whether this is the first or the last rank
+    ///```
+    /// use athena_chess::game::*;
+    /// assert!(Rank::One.is_on_edge());
+    /// assert!(Rank::Eight.is_on_edge());
+    /// assert!(!Rank::Seven.is_on_edge());
+    ///```
+    pub fn is_on_edge(&self) -> bool {
+        matches!(self, Rank::One | Rank::Eight)
+    }
+}
+
+/// the color of a square on the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SquareColor {
+    Light,
+    Dark,
+}
+
 /// represents a square on a chess board. Can be in Range from 0 to 63
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "usize", into = "usize")
+)]
 pub struct Square(u8);
 
+impl From<Square> for usize {
+    fn from(square: Square) -> Self {
+        square.as_index()
+    }
+}
+
+/// shares its validation with Square::new. Values that don't even fit into a u8 are reported as
+/// square 255.
+///```
+/// use athena_chess::game::*;
+/// assert_eq!(Square::try_from(0_usize).unwrap(), A1);
+/// assert_eq!(Square::try_from(63_usize).unwrap(), H8);
+/// assert!(Square::try_from(64_usize).is_err());
+/// assert!(Square::try_from(256_usize).is_err());
+///```
 impl TryFrom<usize> for Square {
     type Error = ChessError;
     fn try_from(value: usize) -> Result<Self, Self::Error> {
-        if value < 64 {
-            Ok(Square(value as u8))
-        } else {
-            Err(ChessError::InvalidSquare { square: value as u8 })
+        Self::new(u8::try_from(value).unwrap_or(u8::MAX))
+    }
+}
+
+/// parses algebraic coordinates like e4. The file may be upper case. Like TryFrom, invalid input
+/// is reported as square 255.
+///```
+/// use athena_chess::game::*;
+/// assert_eq!("e4".parse::<Square>().unwrap(), E4);
+/// assert_eq!("a1".parse::<Square>().unwrap(), A1);
+/// assert_eq!("H8".parse::<Square>().unwrap(), H8);
+/// assert!("i9".parse::<Square>().is_err());
+/// assert!("e9".parse::<Square>().is_err());
+/// assert!("e".parse::<Square>().is_err());
+/// assert!("e44".parse::<Square>().is_err());
+///```
+impl FromStr for Square {
+    type Err = ChessError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.as_bytes() {
+            [file, rank @ b'1'..=b'8'] if (b'a'..=b'h').contains(&file.to_ascii_lowercase()) => {
+                Self::new((rank - b'1') * 8 + (file.to_ascii_lowercase() - b'a'))
+            }
+            _ => Err(ChessError::InvalidSquare { square: u8::MAX }),
         }
     }
 }
@@ -123,6 +230,38 @@ impl Square {
         }
     }
 
+    /// all squares from A1 to H8, going through the ranks one after another
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(Square::all().count(), 64);
+    /// assert_eq!(Square::all().next(), Some(A1));
+    /// assert_eq!(Square::all().nth(8), Some(A2));
+    /// assert_eq!(Square::all().last(), Some(H8));
+    ///```
+    pub fn all() -> impl DoubleEndedIterator<Item = Square> {
+        (0..64).map(Self)
+    }
+
+    /// the squares of a rank from the A to the H file
+    ///```
+    /// use athena_chess::game::*;
+    /// let squares: Vec<Square> = Square::iter_rank(Rank::Two).collect();
+    /// assert_eq!(squares, [A2, B2, C2, D2, E2, F2, G2, H2]);
+    ///```
+    pub fn iter_rank(rank: Rank) -> impl DoubleEndedIterator<Item = Square> {
+        File::all().map(move |file| Self::from_rank_file(rank, file))
+    }
+
+    /// the squares of a file from the first to the eighth rank
+    ///```
+    /// use athena_chess::game::*;
+    /// let squares: Vec<Square> = Square::iter_file(File::C).collect();
+    /// assert_eq!(squares, [C1, C2, C3, C4, C5, C6, C7, C8]);
+    ///```
+    pub fn iter_file(file: File) -> impl DoubleEndedIterator<Item = Square> {
+        Rank::all().map(move |rank| Self::from_rank_file(rank, file))
+    }
+
     ///```
     /// use athena_chess::game::*;
     /// assert_eq!(Square::from_rank_file(Rank::Four, File::A), A4);
@@ -248,6 +387,60 @@ impl Square {
     pub fn get_delta_file(&self, other: Self) -> i8 {
         other.get_file() as i8 - self.get_file() as i8
     }
+
+    /// whether self is a light or a dark square
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(A1.square_color(), SquareColor::Dark);
+    /// assert_eq!(H1.square_color(), SquareColor::Light);
+    /// assert_eq!(A8.square_color(), SquareColor::Light);
+    /// assert_eq!(H8.square_color(), SquareColor::Dark);
+    ///```
+    pub fn square_color(&self) -> SquareColor {
+        if (self.get_rank() as u8 + self.get_file() as u8).is_multiple_of(2) {
+            SquareColor::Dark
+        } else {
+            SquareColor::Light
+        }
+    }
+
+    /// the number of king moves needed to get from self to other
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(A1.chebyshev_distance(H8), 7);
+    /// assert_eq!(A1.chebyshev_distance(B3), 2);
+    /// assert_eq!(E4.chebyshev_distance(E4), 0);
+    ///```
+    pub fn chebyshev_distance(&self, other: Self) -> u8 {
+        let (delta_rank, delta_file) = (self.get_delta_rank(other), self.get_delta_file(other));
+        delta_rank.unsigned_abs().max(delta_file.unsigned_abs())
+    }
+
+    /// the number of rook steps of length one needed to get from self to other
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(A1.manhattan_distance(H8), 14);
+    /// assert_eq!(A1.manhattan_distance(B3), 3);
+    /// assert_eq!(E4.manhattan_distance(E4), 0);
+    ///```
+    pub fn manhattan_distance(&self, other: Self) -> u8 {
+        self.get_delta_rank(other).unsigned_abs() + self.get_delta_file(other).unsigned_abs()
+    }
+
+    /// whether both squares lie on the same diagonal or anti diagonal. A square is not on a
+    /// diagonal with itself.
+    ///```
+    /// use athena_chess::game::*;
+    /// assert!(A1.same_diagonal(H8));
+    /// assert!(H1.same_diagonal(A8));
+    /// assert!(C6.same_diagonal(E4));
+    /// assert!(!A1.same_diagonal(B3));
+    /// assert!(!A1.same_diagonal(A8));
+    /// assert!(!E4.same_diagonal(E4));
+    ///```
+    pub fn same_diagonal(&self, other: Self) -> bool {
+        *self != other && self.get_delta_rank(other).abs() == self.get_delta_file(other).abs()
+    }
 }
 
 impl Display for Square {
@@ -0,0 +1,272 @@
+use std::io;
+use std::path::Path;
+
+use rand::seq::IndexedRandom;
+
+use super::*;
+
+mod keys;
+
+// every entry of a polyglot book takes 16 bytes
+const ENTRY_SIZE: usize = 16;
+
+/// an entry of a polyglot book. All values are stored big endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BookEntry {
+    key: u64,
+    mv: u16,
+    weight: u16,
+}
+
+/// an opening book in the polyglot .bin format, a list of entries sorted by the hash of the
+/// position they belong to.
+#[derive(Debug, Clone, Default)]
+pub struct OpeningBook {
+    entries: Vec<BookEntry>,
+}
+
+impl OpeningBook {
+    /// reads a polyglot book from the file at path
+    pub fn from_polyglot(path: &Path) -> io::Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// reads a polyglot book from its bytes. Each entry is a 64 bit key, a 16 bit move, a 16 bit
+    /// weight and 32 bits of learning data, which is ignored.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the book size {} is not a multiple of {ENTRY_SIZE}", bytes.len()),
+            ));
+        }
+        let mut entries: Vec<BookEntry> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|chunk| BookEntry {
+                key: u64::from_be_bytes(chunk[0..8].try_into().unwrap()),
+                mv: u16::from_be_bytes(chunk[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(chunk[10..12].try_into().unwrap()),
+            })
+            .collect();
+        // books are sorted already, but the lookup relies on it
+        entries.sort_by_key(|entry| entry.key);
+        Ok(Self { entries })
+    }
+
+    /// the number of entries in the book
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// the book moves stored under the polyglot key of the position in game, with their weights.
+    /// Entries that aren't legal in game are skipped.
+    pub fn moves(&self, game: &Game, key: u64) -> Vec<(Move, u16)> {
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let legal = game.get_available_moves();
+        self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.key == key)
+            .filter_map(|entry| decode_move(game, &legal, entry.mv).map(|mv| (mv, entry.weight)))
+            .collect()
+    }
+
+    /// picks one of the book moves for the position in game at random, moves with a higher weight
+    /// being picked more often. Returns None if the position isn't in the book.
+    pub fn probe(&self, game: &Game) -> Option<Move> {
+        self.probe_key(game, polyglot_key(game), &mut rand::rng())
+    }
+
+    /// like probe, but looks up the moves stored under key and picks one with rng, so the choice
+    /// can be reproduced with a seeded rng
+    pub fn probe_key(&self, game: &Game, key: u64, rng: &mut impl rand::Rng) -> Option<Move> {
+        let moves = self.moves(game, key);
+        match moves.choose_weighted(rng, |(_, weight)| *weight) {
+            Ok((mv, _)) => Some(*mv),
+            // all moves have a weight of zero
+            Err(_) => moves.first().map(|(mv, _)| *mv),
+        }
+    }
+}
+
+/// the hash of the position in game that polyglot books are keyed by. It covers the pieces, the
+/// castling rights, the player to move and the en passant file, but the latter only if a pawn of
+/// the player to move stands next to the pawn that can be taken.
+///```
+/// use athena_chess::game::*;
+/// assert_eq!(book::polyglot_key(&Game::init()), 0x463b_9618_1691_fc9c);
+///```
+pub fn polyglot_key(game: &Game) -> u64 {
+    let mut key = 0;
+    for square in Square::all() {
+        if let Some(&(piece, color)) = game.board.get_piece_on_square(square) {
+            let kind = match piece {
+                Piece::Pawn => 0,
+                Piece::Knight => 1,
+                Piece::Bishop => 2,
+                Piece::Rook { .. } => 3,
+                Piece::Queen => 4,
+                Piece::King { .. } => 5,
+            };
+            key ^= keys::RANDOM64[64 * (2 * kind + color.is_white() as usize) + square.as_index()];
+        }
+    }
+    let castling = game.castling_rights();
+    for (i, right) in [CastlingRights::WK, CastlingRights::WQ, CastlingRights::BK, CastlingRights::BQ]
+        .into_iter()
+        .enumerate()
+    {
+        if castling.contains(right) {
+            key ^= keys::RANDOM64[768 + i];
+        }
+    }
+    if let Some(file) = game.en_passant_file() {
+        key ^= keys::RANDOM64[772 + file as usize];
+    }
+    if game.turn.is_white() {
+        key ^= keys::RANDOM64[780];
+    }
+    key
+}
+
+/// turns the polyglot encoding of a move into the legal move it stands for. The bits hold the
+/// target file and rank, the starting file and rank and the promotion piece. Castling is stored as
+/// the king taking its own rook.
+fn decode_move(game: &Game, legal: &[Move], encoded: u16) -> Option<Move> {
+    let square = |bits: u16| Square::new((((bits >> 3) & 7) * 8 + (bits & 7)) as u8).ok();
+    let from = square(encoded >> 6)?;
+    let mut to = square(encoded)?;
+    let promotion = match (encoded >> 12) & 7 {
+        0 => None,
+        1 => Some(Piece::Knight),
+        2 => Some(Piece::Bishop),
+        3 => Some(Piece::Rook { has_moved: true }),
+        4 => Some(Piece::Queen),
+        _ => return None,
+    };
+
+    if let Some((Piece::King { .. }, _)) = game.board.get_piece_on_square(from)
+        && from.get_file() == File::E
+        && from.get_rank() == to.get_rank()
+    {
+        to = match to.get_file() {
+            File::H => Square::from_rank_file(from.get_rank(), File::G),
+            File::A => Square::from_rank_file(from.get_rank(), File::C),
+            _ => to,
+        };
+    }
+
+    let promotion = promotion.as_ref().map(std::mem::discriminant);
+    legal
+        .iter()
+        .find(|mv| mv.get_from() == from && mv.get_to() == to && mv.get_promotion().as_ref().map(std::mem::discriminant) == promotion)
+        .copied()
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    // the polyglot key of the starting position
+    const START_KEY: u64 = 0x463b_9618_1691_fc9c;
+
+    fn encode(from: Square, to: Square, promotion: u16) -> u16 {
+        let bits = |sq: Square| (sq.get_rank() as u16) << 3 | sq.get_file() as u16;
+        promotion << 12 | bits(from) << 6 | bits(to)
+    }
+
+    fn book(entries: &[(u64, u16, u16)]) -> OpeningBook {
+        let mut bytes = Vec::new();
+        for (key, mv, weight) in entries {
+            bytes.extend(key.to_be_bytes());
+            bytes.extend(mv.to_be_bytes());
+            bytes.extend(weight.to_be_bytes());
+            bytes.extend(0_u32.to_be_bytes());
+        }
+        OpeningBook::from_bytes(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_book_moves() {
+        let book = book(&[
+            (START_KEY + 1, encode(G1, F3, 0), 5),
+            (START_KEY, encode(E2, E4, 0), 3),
+            (START_KEY, encode(D2, D4, 0), 1),
+            (START_KEY - 1, encode(C2, C4, 0), 2),
+        ]);
+        assert_eq!(book.len(), 4);
+        let game = Game::init();
+        assert_eq!(
+            book.moves(&game, START_KEY),
+            vec![(Move::new(Piece::Pawn, E2, E4, None), 3), (Move::new(Piece::Pawn, D2, D4, None), 1)]
+        );
+        let mv = book.probe(&game).unwrap();
+        assert!(mv == Move::new(Piece::Pawn, E2, E4, None) || mv == Move::new(Piece::Pawn, D2, D4, None));
+        let mut rng = StdRng::seed_from_u64(3);
+        assert_eq!(book.probe_key(&game, 42, &mut rng), None);
+        // e2e4 has three times the weight of d2d4
+        let e4 = (0..400)
+            .filter(|_| book.probe_key(&game, START_KEY, &mut rng) == Some(Move::new(Piece::Pawn, E2, E4, None)))
+            .count();
+        assert!((250..350).contains(&e4), "{e4}");
+        assert_eq!(book.probe(&Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap()), None);
+    }
+
+    #[test]
+    fn test_decode_castling_and_promotion() {
+        let game = Game::from_fen("4k3/P7/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        let legal = game.get_available_moves();
+        assert_eq!(
+            decode_move(&game, &legal, encode(E1, H1, 0)),
+            Some(Move::new(Piece::King { has_moved: false }, E1, G1, None))
+        );
+        assert_eq!(
+            decode_move(&game, &legal, encode(A7, A8, 4)),
+            Some(Move::promotions(A7, A8, None)[0])
+        );
+        assert_eq!(decode_move(&game, &legal, encode(A7, A8, 0)), None);
+    }
+
+    #[test]
+    fn test_book_size_must_fit_entries() {
+        assert!(OpeningBook::from_bytes(&[0; 17]).is_err());
+        assert!(OpeningBook::from_bytes(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_polyglot_key() {
+        // the reference keys of the polyglot format
+        for (fen, key) in [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", START_KEY),
+            ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c_9b50_fd11_4196),
+            (
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                0x0756_b944_61c5_0fb0,
+            ),
+            ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662f_afb9_65db_29d4),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                0x22a4_8b5a_8e47_ff78,
+            ),
+            ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 1 3", 0x652a_607c_a3f2_42c1),
+            ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 2 4", 0x00fd_d303_c946_bdd9),
+            (
+                "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+                0x3c81_23ea_7b06_7637,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 1 4",
+                0x5c3f_9b82_9b27_9560,
+            ),
+        ] {
+            assert_eq!(polyglot_key(&Game::from_fen(fen).unwrap()), key, "{fen}");
+        }
+        assert_eq!(polyglot_key(&Game::init()), START_KEY);
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt::Display;
+use std::ops::BitOr;
+
+use super::*;
+
+/// the castling moves that are still allowed, like the castling field of a fen. A right is lost
+/// for good once the king or the rook moves or the rook is taken, even if they come back later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastlingRights(u8);
+
+impl BitOr<CastlingRights> for CastlingRights {
+    type Output = CastlingRights;
+    fn bitor(self, rhs: CastlingRights) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl CastlingRights {
+    pub const NONE: Self = Self(0);
+    /// white castling short
+    pub const WK: Self = Self(1);
+    /// white castling long
+    pub const WQ: Self = Self(2);
+    /// black castling short
+    pub const BK: Self = Self(4);
+    /// black castling long
+    pub const BQ: Self = Self(8);
+    pub const ALL: Self = Self(15);
+
+    /// the right of color to castle short
+    pub fn kingside(color: Color) -> Self {
+        if color.is_white() { Self::WK } else { Self::BK }
+    }
+
+    /// the right of color to castle long
+    pub fn queenside(color: Color) -> Self {
+        if color.is_white() { Self::WQ } else { Self::BQ }
+    }
+
+    /// whether all of the rights in other are set
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// parses the castling field of a fen, f.e. "KQkq", "Kq" or "-"
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(CastlingRights::from_fen("Kq"), Some(CastlingRights::WK | CastlingRights::BQ));
+    /// assert_eq!(CastlingRights::from_fen("-"), Some(CastlingRights::NONE));
+    /// assert_eq!(CastlingRights::from_fen("KX"), None);
+    ///```
+    pub fn from_fen(field: &str) -> Option<Self> {
+        if field == "-" {
+            return Some(Self::NONE);
+        }
+        if field.is_empty() {
+            return None;
+        }
+        field.chars().try_fold(Self::NONE, |rights, c| {
+            let right = match c {
+                'K' => Self::WK,
+                'Q' => Self::WQ,
+                'k' => Self::BK,
+                'q' => Self::BQ,
+                _ => return None,
+            };
+            Some(rights | right)
+        })
+    }
+
+    /// the same rights with the colors swapped, for a mirrored board
+    ///```
+    /// use athena_chess::game::*;
+    /// let rights = CastlingRights::WK | CastlingRights::BQ;
+    /// assert_eq!(rights.mirror(), CastlingRights::BK | CastlingRights::WQ);
+    ///```
+    pub fn mirror(&self) -> Self {
+        // white rights are the lower two bits, black ones the upper two
+        Self((self.0 & 3) << 2 | self.0 >> 2)
+    }
+
+    /// the rights that are lost when a piece moves from or to square: the king leaving its square
+    /// gives up both rights of its color, a rook leaving or being taken on its corner the right on
+    /// its side
+    pub(crate) fn lost_on(square: Square) -> Self {
+        match square {
+            E1 => Self::WK | Self::WQ,
+            H1 => Self::WK,
+            A1 => Self::WQ,
+            E8 => Self::BK | Self::BQ,
+            H8 => Self::BK,
+            A8 => Self::BQ,
+            _ => Self::NONE,
+        }
+    }
+}
+
+/// the castling field of a fen
+impl Display for CastlingRights {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "-");
+        }
+        for (right, c) in [(Self::WK, 'K'), (Self::WQ, 'Q'), (Self::BK, 'k'), (Self::BQ, 'q')] {
+            if self.contains(right) {
+                write!(f, "{c}")?;
+            }
+        }
+        Ok(())
+    }
+}
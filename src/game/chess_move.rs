@@ -1,18 +1,50 @@
 use std::fmt::Display;
 
+use super::Game;
 use super::board::piece::Piece;
 use super::board::square::Square;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Move {
     piece: Piece,
     from: Square,
     to: Square,
     takes: Option<Piece>,
+    // the piece a pawn turns into when reaching the last rank
+    promotion: Option<Piece>,
 }
 impl Move {
     pub fn new(piece: Piece, from: Square, to: Square, takes: Option<Piece>) -> Self {
-        Self { piece, from, to, takes }
+        Self {
+            piece,
+            from,
+            to,
+            takes,
+            promotion: None,
+        }
+    }
+    /// creates the move of a pawn from from to to, promoting to piece. A promoted rook can't castle,
+    /// so its has_moved flag is always set and doesn't matter when comparing moves.
+    ///```
+    /// use athena_chess::game::*;
+    /// let rook = Move::promotion(E7, E8, None, Piece::Rook { has_moved: false });
+    /// assert_eq!(rook.get_promotion(), Some(Piece::Rook { has_moved: true }));
+    /// assert_eq!(rook, Move::promotions(E7, E8, None)[1]);
+    ///```
+    pub fn promotion(from: Square, to: Square, takes: Option<Piece>, mut piece: Piece) -> Self {
+        piece.make_moved();
+        Self {
+            piece: Piece::Pawn,
+            from,
+            to,
+            takes,
+            promotion: Some(piece),
+        }
+    }
+    /// creates the moves for all four pieces a pawn moving from from to to can promote to
+    pub fn promotions(from: Square, to: Square, takes: Option<Piece>) -> [Self; 4] {
+        [Piece::Queen, Piece::Rook { has_moved: false }, Piece::Bishop, Piece::Knight].map(|piece| Self::promotion(from, to, takes, piece))
     }
     pub fn get_from(&self) -> Square {
         self.from
@@ -23,12 +55,102 @@ impl Move {
     pub fn get_piece(&self) -> Piece {
         self.piece
     }
-    /// sets takes to piece if piece is some or takes is none
-    pub fn set_takes(&mut self, piece: Option<Piece>) {
-        if self.takes.is_some() && piece.is_none() {
-            return;
+    pub fn get_takes(&self) -> Option<Piece> {
+        self.takes
+    }
+    pub fn get_promotion(&self) -> Option<Piece> {
+        self.promotion
+    }
+    /// whether the move takes a piece, which includes promotions taking a piece and en passant
+    ///```
+    /// use athena_chess::game::*;
+    /// assert!(Move::new(Piece::Pawn, E5, D6, Some(Piece::Pawn)).is_capture());
+    /// assert!(Move::promotions(B7, A8, Some(Piece::Rook { has_moved: false }))[0].is_capture());
+    /// assert!(!Move::promotions(B7, B8, None)[0].is_capture());
+    /// assert!(!Move::new(Piece::Knight, G1, F3, None).is_capture());
+    ///```
+    pub fn is_capture(&self) -> bool {
+        self.takes.is_some()
+    }
+    /// whether the move takes a pawn en passant in game, the position before the move: a pawn
+    /// capture to an empty square. A move alone can't tell, it looks like any other pawn capture.
+    ///```
+    /// use athena_chess::game::*;
+    /// let game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    /// let en_passant = Move::new(Piece::Pawn, E5, D6, Some(Piece::Pawn));
+    /// assert!(game.get_available_moves().contains(&en_passant));
+    /// assert!(en_passant.is_en_passant(&game) && en_passant.is_capture());
+    ///
+    /// let game = Game::from_fen("4k3/8/3n4/4P3/8/8/8/4K3 w - - 0 1").unwrap();
+    /// let capture = Move::new(Piece::Pawn, E5, D6, Some(Piece::Knight));
+    /// assert!(!capture.is_en_passant(&game) && capture.is_capture());
+    /// assert!(!Move::new(Piece::Pawn, E5, E6, None).is_en_passant(&game));
+    ///```
+    pub fn is_en_passant(&self, game: &Game) -> bool {
+        self.piece.is_pawn() && self.is_capture() && !game.board.is_occupied(self.to)
+    }
+    /// whether a pawn promotes with the move
+    pub fn is_promotion(&self) -> bool {
+        self.promotion.is_some()
+    }
+    /// whether the move castles, which is written as the king moving two squares
+    ///```
+    /// use athena_chess::game::*;
+    /// assert!(Move::new(Piece::King { has_moved: false }, E1, G1, None).is_castle());
+    /// assert!(!Move::new(Piece::King { has_moved: false }, E1, F1, None).is_castle());
+    ///```
+    pub fn is_castle(&self) -> bool {
+        self.piece.is_king() && self.from.get_delta_file(self.to).abs() == 2
+    }
+    /// whether both moves move the same kind of piece between the same squares and promote to the
+    /// same kind of piece. What they take and the has_moved flags are ignored, so a move entered by
+    /// a user matches the generated move, which knows what it captures.
+    ///```
+    /// use athena_chess::game::*;
+    /// let entered = Move::new(Piece::Pawn, E4, D5, None);
+    /// let generated = Move::new(Piece::Pawn, E4, D5, Some(Piece::Knight));
+    /// assert!(entered.matches(&generated));
+    /// assert!(!entered.matches(&Move::new(Piece::Pawn, E4, E5, None)));
+    /// assert!(!Move::promotions(E7, E8, None)[0].matches(&Move::promotions(E7, E8, None)[3]));
+    ///```
+    pub fn matches(&self, other: &Move) -> bool {
+        let kind = |piece: &Piece| std::mem::discriminant(piece);
+        self.from == other.from
+            && self.to == other.to
+            && kind(&self.piece) == kind(&other.piece)
+            && self.promotion.as_ref().map(kind) == other.promotion.as_ref().map(kind)
+    }
+    /// formats the move in the long algebraic notation used by UCI, like e2e4, e1g1 for castling or
+    /// e7e8q for promotions. See Game::parse_uci_move for the other direction.
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(Move::new(Piece::Knight, G1, F3, None).to_uci(), "g1f3");
+    /// assert_eq!(Move::promotions(E7, E8, None)[0].to_uci(), "e7e8q");
+    ///```
+    pub fn to_uci(&self) -> String {
+        let promotion = match self.promotion {
+            Some(Piece::Queen) => "q",
+            Some(Piece::Rook { .. }) => "r",
+            Some(Piece::Bishop) => "b",
+            Some(Piece::Knight) => "n",
+            _ => "",
+        };
+        format!("{}{}{promotion}", self.from, self.to)
+    }
+    /// returns the move taking piece on the way. A promotion stays a promotion, so a pawn taking on
+    /// the last rank both captures and promotes. Panics for castling, which can't take anything.
+    ///```
+    /// use athena_chess::game::*;
+    /// let mv = Move::promotions(B7, A8, None)[0].with_capture(Piece::Rook { has_moved: false });
+    /// assert_eq!(mv.get_takes(), Some(Piece::Rook { has_moved: false }));
+    /// assert_eq!(mv.get_promotion(), Some(Piece::Queen));
+    ///```
+    pub fn with_capture(self, piece: Piece) -> Self {
+        assert!(!self.is_castle(), "castling can't capture");
+        Self {
+            takes: Some(piece),
+            ..self
         }
-        self.takes = piece;
     }
 }
 
@@ -41,6 +163,10 @@ impl Display for Move {
             self.from,
             self.takes.map(|_| "x").unwrap_or(""),
             self.to
-        )
+        )?;
+        if let Some(promotion) = self.promotion {
+            write!(f, "={promotion}")?;
+        }
+        Ok(())
     }
 }
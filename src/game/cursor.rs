@@ -0,0 +1,163 @@
+use super::*;
+
+// how many plies lie between two stored positions
+const SNAPSHOT_INTERVAL: usize = 16;
+
+/// steps through the moves of a game without changing the game itself, f.e. to scrub through a
+/// finished game in a gui. Every SNAPSHOT_INTERVAL plies a copy of the position is kept, so going
+/// to a ply only replays the moves since the closest one.
+#[derive(Debug, Clone)]
+pub struct GameCursor {
+    moves: Vec<Move>,
+    // the positions at ply 0, SNAPSHOT_INTERVAL, 2 * SNAPSHOT_INTERVAL, ...
+    snapshots: Vec<Game>,
+    position: Game,
+    ply: usize,
+}
+
+impl GameCursor {
+    /// creates a cursor at the start position, which steps through the moves played from start.
+    /// Fails with the reason if one of the moves isn't legal.
+    ///```
+    /// use athena_chess::game::*;
+    /// let moves = [Move::new(Piece::Pawn, E2, E4, None), Move::new(Piece::Pawn, E7, E5, None)];
+    /// let mut cursor = GameCursor::new(Game::init(), moves.to_vec()).unwrap();
+    /// assert!(cursor.next());
+    /// assert_eq!(cursor.ply(), 1);
+    /// assert!(cursor.goto(2));
+    /// assert!(!cursor.next());
+    ///```
+    pub fn new(start: Game, moves: Vec<Move>) -> Result<Self, IllegalMoveError> {
+        let mut game = start.clone();
+        let mut snapshots = vec![start.clone()];
+        for (i, mv) in moves.iter().enumerate() {
+            game.execute_move(*mv)?;
+            if (i + 1).is_multiple_of(SNAPSHOT_INTERVAL) {
+                snapshots.push(game.clone());
+            }
+        }
+        Ok(Self {
+            moves,
+            snapshots,
+            position: start,
+            ply: 0,
+        })
+    }
+
+    /// the position after the moves up to the current ply
+    pub fn position(&self) -> &Game {
+        &self.position
+    }
+
+    /// the number of moves played in the current position
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// the number of moves the cursor can step through
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// goes to the position after ply moves. Returns false and stays put if there are fewer moves.
+    pub fn goto(&mut self, ply: usize) -> bool {
+        if ply > self.moves.len() {
+            return false;
+        }
+        // replaying from the current position is cheaper than going back to a snapshot
+        if ply < self.ply || ply - self.ply > SNAPSHOT_INTERVAL {
+            let snapshot = ply / SNAPSHOT_INTERVAL;
+            self.position = self.snapshots[snapshot].clone();
+            self.ply = snapshot * SNAPSHOT_INTERVAL;
+        }
+        for mv in &self.moves[self.ply..ply] {
+            // the moves were checked when the cursor was created
+            self.position.apply_move(*mv);
+        }
+        self.ply = ply;
+        true
+    }
+
+    /// steps one move forward. Returns false at the end of the game.
+    // the cursor moves over positions, it doesn't yield them like an iterator would
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> bool {
+        self.goto(self.ply + 1)
+    }
+
+    /// steps one move back. Returns false at the start of the game.
+    pub fn prev(&mut self) -> bool {
+        self.ply > 0 && self.goto(self.ply - 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn italian_game() -> GameCursor {
+        let mut game = Game::init();
+        let mut moves = Vec::new();
+        for san in ["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5"] {
+            let mv = game.parse_san_move(san).unwrap();
+            game.execute_move(mv).unwrap();
+            moves.push(mv);
+        }
+        GameCursor::new(Game::init(), moves).unwrap()
+    }
+
+    #[test]
+    fn test_goto_italian_game() {
+        let mut cursor = italian_game();
+        assert_eq!(cursor.len(), 6);
+        assert!(cursor.goto(4));
+        let expected = Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+        assert_eq!(cursor.position().board, expected.board);
+        assert_eq!(cursor.position().turn, Color::White);
+
+        assert!(cursor.next());
+        assert!(cursor.prev());
+        assert!(cursor.prev());
+        assert_eq!(cursor.ply(), 3);
+        assert_eq!(cursor.position().turn, Color::Black);
+        assert!(!cursor.goto(7));
+        assert_eq!(cursor.ply(), 3);
+        assert!(cursor.goto(0));
+        assert!(!cursor.prev());
+        assert_eq!(cursor.position().board, Game::init().board);
+    }
+
+    #[test]
+    fn test_goto_across_snapshots() {
+        // knights going back and forth, so there are enough moves for several snapshots
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        let mut game = Game::init();
+        let mut moves = Vec::new();
+        for uci in shuffle.iter().cycle().take(40) {
+            let mv = game.parse_uci_move(uci).unwrap();
+            game.apply_move(mv);
+            moves.push(mv);
+        }
+        let mut cursor = GameCursor::new(Game::init(), moves.clone()).unwrap();
+        for ply in [37, 5, 40, 16, 17, 0, 33] {
+            assert!(cursor.goto(ply));
+            let mut reference = Game::init();
+            moves[..ply].iter().for_each(|mv| reference.apply_move(*mv));
+            assert_eq!(cursor.position().zobrist_hash(), reference.zobrist_hash(), "ply {ply}");
+            assert_eq!(cursor.position().moves, reference.moves, "ply {ply}");
+        }
+        // the knights are back home every four plies
+        assert!(cursor.goto(36));
+        assert_eq!(cursor.position().board, Game::init().board);
+    }
+
+    #[test]
+    fn test_illegal_moves_are_rejected() {
+        let moves = vec![Move::new(Piece::Pawn, E2, E5, None)];
+        assert!(GameCursor::new(Game::init(), moves).is_err());
+    }
+}
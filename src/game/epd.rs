@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use super::error::EpdError;
+use super::*;
+
+/// the operations of an epd record. The bm and am operations are parsed into moves, everything
+/// else is kept as written, with quotes removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpdOps {
+    /// the best moves of the bm operation
+    pub best_moves: Vec<Move>,
+    /// the moves to avoid of the am operation
+    pub avoid_moves: Vec<Move>,
+    ops: HashMap<String, Vec<String>>,
+}
+
+impl EpdOps {
+    /// the operands of the operation, f.e. ["WAC.001"] for id "WAC.001"
+    pub fn get(&self, opcode: &str) -> Option<&[String]> {
+        self.ops.get(opcode).map(Vec::as_slice)
+    }
+
+    /// the id of the position, if there is one
+    pub fn id(&self) -> Option<&str> {
+        self.get("id")?.first().map(String::as_str)
+    }
+}
+
+/// parses a line of extended position description, the first four fields of a fen followed by
+/// operations like bm Qd1+; or id "WAC.001";. The move counters are taken from the hmvc and fmvn
+/// operations if present.
+///```
+/// use athena_chess::game::*;
+/// use athena_chess::game::epd::parse_epd;
+/// let (game, ops) = parse_epd("7k/8/8/8/8/8/8/R6K w - - bm Ra8#; id \"mate\";").unwrap();
+/// assert_eq!(ops.best_moves, vec![game.parse_san_move("Ra8").unwrap()]);
+/// assert_eq!(ops.id(), Some("mate"));
+///```
+pub fn parse_epd(line: &str) -> Result<(Game, EpdOps), EpdError> {
+    let mut rest = line.trim();
+    let mut fields = Vec::new();
+    for _ in 0..4 {
+        let (field, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        if field.is_empty() {
+            return Err(EpdError::MissingFields { found: fields.len() });
+        }
+        fields.push(field);
+        rest = tail.trim_start();
+    }
+
+    let mut ops = HashMap::new();
+    for op in split_operations(rest)? {
+        let (opcode, operands) = op.split_first().ok_or(EpdError::MalformedOperation)?;
+        ops.insert(opcode.clone(), operands.to_vec());
+    }
+
+    let counter = |opcode: &str, default: &str| match ops.get(opcode) {
+        Some(operands) => operands.first().cloned().ok_or(EpdError::MalformedOperation),
+        None => Ok(default.to_string()),
+    };
+    let fen = format!("{} {} {}", fields.join(" "), counter("hmvc", "0")?, counter("fmvn", "1")?);
+    let game = Game::from_fen(&fen).map_err(|e| EpdError::InvalidFen { e })?;
+
+    let moves = |opcode: &str| -> Result<Vec<Move>, EpdError> {
+        ops.get(opcode)
+            .into_iter()
+            .flatten()
+            .map(|san| game.parse_san_move(san).map_err(|e| EpdError::InvalidMove { san: san.clone(), e }))
+            .collect()
+    };
+    let best_moves = moves("bm")?;
+    let avoid_moves = moves("am")?;
+    Ok((
+        game,
+        EpdOps {
+            best_moves,
+            avoid_moves,
+            ops,
+        },
+    ))
+}
+
+/// splits the operations at the semicolons ending them and each operation into its opcode and
+/// operands. Quoted operands may contain spaces and semicolons.
+fn split_operations(s: &str) -> Result<Vec<Vec<String>>, EpdError> {
+    let mut ops = Vec::new();
+    let mut op = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    for c in s.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            _ if in_quotes => token.push(c),
+            ';' | ' ' | '\t' => {
+                if !token.is_empty() || quoted {
+                    op.push(std::mem::take(&mut token));
+                    quoted = false;
+                }
+                if c == ';' {
+                    if op.is_empty() {
+                        return Err(EpdError::MalformedOperation);
+                    }
+                    ops.push(std::mem::take(&mut op));
+                }
+            }
+            _ => token.push(c),
+        }
+    }
+    // every operation has to be terminated by a semicolon
+    if in_quotes || !token.is_empty() || !op.is_empty() {
+        return Err(EpdError::MalformedOperation);
+    }
+    Ok(ops)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_epd() {
+        let line = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";";
+        let (game, ops) = parse_epd(line).unwrap();
+        assert_eq!(ops.best_moves, vec![Move::new(Piece::Queen, G3, G6, None)]);
+        assert!(ops.avoid_moves.is_empty());
+        assert_eq!(ops.id(), Some("WAC.001"));
+        assert_eq!(ops.get("bm"), Some(&["Qg6".to_string()][..]));
+        assert_eq!(game.halfmove_clock, 0);
+    }
+
+    #[test]
+    fn test_parse_epd_operations() {
+        let line = "4k3/8/8/8/8/8/8/R3K2R w KQ - am O-O Ra8+; c0 \"a; quoted comment\"; hmvc 12; fmvn 40;";
+        let (game, ops) = parse_epd(line).unwrap();
+        assert_eq!(
+            ops.avoid_moves,
+            vec![
+                Move::new(Piece::King { has_moved: false }, E1, G1, None),
+                Move::new(Piece::Rook { has_moved: false }, A1, A8, None)
+            ]
+        );
+        assert_eq!(ops.get("c0"), Some(&["a; quoted comment".to_string()][..]));
+        assert_eq!(game.halfmove_clock, 12);
+        assert_eq!(ops.id(), None);
+    }
+
+    #[test]
+    fn test_parse_epd_errors() {
+        assert_eq!(
+            parse_epd("4k3/8/8/8/8/8/8/4K3 w -").unwrap_err(),
+            EpdError::MissingFields { found: 3 }
+        );
+        assert_eq!(
+            parse_epd("4k3/8/8/8/8/8/8/4K3 w - - id \"x\"").unwrap_err(),
+            EpdError::MalformedOperation
+        );
+        assert!(matches!(
+            parse_epd("4k3/8/8/8/8/8/8/4K3 x - - id \"x\";").unwrap_err(),
+            EpdError::InvalidFen { .. }
+        ));
+        assert_eq!(
+            parse_epd("4k3/8/8/8/8/8/8/4K3 w - - bm Qd1;").unwrap_err(),
+            EpdError::InvalidMove {
+                san: "Qd1".to_string(),
+                e: MoveParseError::NoMatchingMove
+            }
+        );
+    }
+}
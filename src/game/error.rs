@@ -38,4 +38,100 @@ pub enum IllegalMoveError {
 
     #[error("cant do the move: {mv}, the square: {square} is blocked")]
     Blocked { mv: Move, square: Square },
+
+    #[error("the game is already over.")]
+    GameOver,
+}
+
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+pub enum FenError {
+    #[error("a fen needs between 4 and 6 fields, found: {found}.")]
+    WrongFieldCount { found: usize },
+
+    #[error("invalid piece placement on rank: {rank}.")]
+    InvalidPlacement { rank: usize },
+
+    #[error("invalid piece character: {c}.")]
+    InvalidPiece { c: char },
+
+    #[error("invalid side to move.")]
+    InvalidColor,
+
+    #[error("invalid castling rights.")]
+    InvalidCastling,
+
+    #[error("invalid en passant square.")]
+    InvalidEnPassant,
+
+    #[error("invalid move counter.")]
+    InvalidCounter,
+
+    #[error("illegal position: {e}")]
+    IllegalPosition { e: PositionError },
+}
+
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+pub enum MoveParseError {
+    #[error("invalid move format, expected something like e2e4 or e7e8q.")]
+    InvalidFormat,
+
+    #[error("invalid promotion piece: {c}.")]
+    InvalidPromotion { c: char },
+
+    #[error("moving from {from} to {to} is not a legal move.")]
+    NotLegal { from: Square, to: Square },
+
+    #[error("moving from {from} to {to} promotes, but no promotion piece was given.")]
+    MissingPromotion { from: Square, to: Square },
+
+    #[error("no legal move matches the given notation.")]
+    NoMatchingMove,
+}
+
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+pub enum PgnError {
+    #[error("invalid tag pair on line: {line}.")]
+    MalformedTag { line: usize },
+
+    #[error("invalid fen tag: {e}")]
+    InvalidFen { e: FenError },
+
+    #[error("invalid move at ply: {ply}: {e}")]
+    UnknownMove { ply: usize, e: MoveParseError },
+}
+
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+pub enum PositionError {
+    #[error("each side needs exactly one king, {color} has: {count}.")]
+    KingCount { color: Color, count: u32 },
+
+    #[error("the kings stand next to each other on {white} and {black}.")]
+    AdjacentKings { white: Square, black: Square },
+
+    #[error("a pawn can't stand on the first or last rank, found one on: {square}.")]
+    PawnOnBackRank { square: Square },
+
+    #[error("{color} is in check, but it's not their turn.")]
+    OpponentInCheck { color: Color },
+}
+
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+pub enum ReplayError {
+    #[error("illegal move at ply: {ply}: {e}")]
+    IllegalMove { ply: usize, e: IllegalMoveError },
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum EpdError {
+    #[error("an epd needs 4 fen fields, found: {found}.")]
+    MissingFields { found: usize },
+
+    #[error("invalid position: {e}")]
+    InvalidFen { e: FenError },
+
+    #[error("operations need an opcode and have to end with a semicolon.")]
+    MalformedOperation,
+
+    #[error("invalid move: {san}: {e}")]
+    InvalidMove { san: String, e: MoveParseError },
 }
@@ -1 +1,761 @@
+use std::sync::LazyLock;
 
+use super::board::Occupancy;
+use super::*;
+
+// the value of the king in the static exchange evaluation, more than all other pieces together
+const SEE_KING_VALUE: i32 = 10_000;
+
+/// every kind of piece, used to walk over the piece masks of the board
+const PIECES: [Piece; 6] = [
+    Piece::Pawn,
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook { has_moved: true },
+    Piece::Queen,
+    Piece::King { has_moved: true },
+];
+
+// piece square tables written from whites point of view with a8 in the top left corner. The values
+// are the commonly used midgame tables of the simplified evaluation function.
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+#[rustfmt::skip]
+const KING_TABLE: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+/// the tunable weights of the evaluation. All values are in centipawns, the default is the
+/// evaluation the engine plays with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalParams {
+    /// centipawns per pseudo legal move of the knights, bishops, rooks and queens
+    pub mobility_weight: i32,
+    /// the values of the pieces, in the order pawn, knight, bishop, rook, queen and king
+    pub piece_values: [i32; 6],
+    /// the piece square tables in the same order, written from whites point of view with a8 first
+    pub piece_square_tables: [[i32; 64]; 6],
+    /// added for every pawn on a file beyond the first one
+    pub doubled_pawn_penalty: i32,
+    /// added for every pawn without friendly pawns on the neighbouring files
+    pub isolated_pawn_penalty: i32,
+    /// the bonus of a passed pawn by the number of ranks it advanced
+    pub passed_pawn_bonus: [i32; 8],
+    /// the bonus for every pawn right in front of a castled king and one rank further ahead
+    pub pawn_shield_bonus: [i32; 2],
+    /// added for every square next to the king that the enemy attacks
+    pub king_zone_attack_penalty: i32,
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        Self {
+            mobility_weight: 4,
+            piece_values: PIECES.map(piece_value),
+            piece_square_tables: [PAWN_TABLE, KNIGHT_TABLE, BISHOP_TABLE, ROOK_TABLE, QUEEN_TABLE, KING_TABLE],
+            doubled_pawn_penalty: DOUBLED_PAWN_PENALTY,
+            isolated_pawn_penalty: ISOLATED_PAWN_PENALTY,
+            passed_pawn_bonus: PASSED_PAWN_BONUS,
+            pawn_shield_bonus: PAWN_SHIELD_BONUS,
+            king_zone_attack_penalty: KING_ZONE_ATTACK_PENALTY,
+        }
+    }
+}
+
+impl EvalParams {
+    /// whether the piece values and piece square tables are the default ones, which the board keeps
+    /// a running score of
+    pub fn has_default_material(&self) -> bool {
+        let default = Self::default();
+        self.piece_values == default.piece_values && self.piece_square_tables == default.piece_square_tables
+    }
+}
+
+// penalty for every pawn on a file beyond the first one
+const DOUBLED_PAWN_PENALTY: i32 = -10;
+// penalty for a pawn without friendly pawns on the neighbouring files
+const ISOLATED_PAWN_PENALTY: i32 = -15;
+// bonus for a pawn no enemy pawn can stop, indexed by how many ranks it has advanced
+const PASSED_PAWN_BONUS: [i32; 8] = [0, 5, 10, 20, 35, 60, 100, 0];
+
+// bonus for each pawn right in front of a castled king and for each pawn one rank further ahead
+const PAWN_SHIELD_BONUS: [i32; 2] = [15, 5];
+// penalty for every square next to the king that the enemy attacks
+const KING_ZONE_ATTACK_PENALTY: i32 = -8;
+// king safety matters less once the queens and most pieces are gone
+const ENDGAME_KING_SAFETY_DIVISOR: i32 = 4;
+
+// below this much material besides pawns and kings on the board, about a rook and a minor piece
+// for each side, the game counts as an endgame
+const ENDGAME_MATERIAL: i32 = 1660;
+
+/// the mask of all squares on the file with the given index
+fn file_mask(file: usize) -> u64 {
+    0x0101_0101_0101_0101 << file
+}
+
+/// the files to the left and right of the file with the given index
+fn adjacent_files_mask(file: usize) -> u64 {
+    let left = if file > 0 { file_mask(file - 1) } else { 0 };
+    let right = if file < 7 { file_mask(file + 1) } else { 0 };
+    left | right
+}
+
+/// the material value of a piece in centipawns. The king is priceless, so it is worth nothing here.
+pub fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 100,
+        Piece::Knight => 320,
+        Piece::Bishop => 330,
+        Piece::Rook { .. } => 500,
+        Piece::Queen => 900,
+        Piece::King { .. } => 0,
+    }
+}
+
+/// the index of the piece in PIECES and in the tables of EvalParams
+fn piece_index(piece: Piece) -> usize {
+    match piece {
+        Piece::Pawn => 0,
+        Piece::Knight => 1,
+        Piece::Bishop => 2,
+        Piece::Rook { .. } => 3,
+        Piece::Queen => 4,
+        Piece::King { .. } => 5,
+    }
+}
+
+/// the bonus for a piece of the given color standing on square. The tables are written from
+/// whites point of view, so they are mirrored for black.
+fn piece_square_value(piece: Piece, color: Color, square: Square, params: &EvalParams) -> i32 {
+    let rank = square.as_index() / 8;
+    let file = square.as_index() % 8;
+    let index = match color {
+        Color::White => (7 - rank) * 8 + file,
+        Color::Black => rank * 8 + file,
+    };
+    params.piece_square_tables[piece_index(piece)][index]
+}
+
+// the material and piece square value of every piece with the default evaluation, by piece_index
+// and color, then by square. Black pieces count negative, so the board can keep a running sum.
+static PIECE_SCORES: LazyLock<[[(i32, i32); 64]; 12]> = LazyLock::new(|| {
+    let params = EvalParams::default();
+    let mut scores = [[(0, 0); 64]; 12];
+    for piece in PIECES {
+        for color in [Color::White, Color::Black] {
+            let sign = if color.is_white() { 1 } else { -1 };
+            for square in Square::all() {
+                scores[piece_index(piece) * 2 + color as usize][square.as_index()] = (
+                    sign * params.piece_values[piece_index(piece)],
+                    sign * piece_square_value(piece, color, square, &params),
+                );
+            }
+        }
+    }
+    scores
+});
+
+/// the material and piece square value the piece adds to the running scores of the board, see
+/// BitBoard::material and BitBoard::placement
+pub(crate) fn piece_score(piece: Piece, color: Color, square: Square) -> (i32, i32) {
+    PIECE_SCORES[piece_index(piece) * 2 + color as usize][square.as_index()]
+}
+
+/// sums up material and piece square bonuses of all pieces of the given color
+fn material_and_position(board: &BitBoard, color: Color, params: &EvalParams) -> i32 {
+    PIECES
+        .iter()
+        .flat_map(|&piece| board.pieces(piece, color).iter().map(move |square| (piece, square)))
+        .map(|(piece, square)| params.piece_values[piece_index(piece)] + piece_square_value(piece, color, square, params))
+        .sum()
+}
+
+/// counts the pseudo legal moves of the knights, bishops, rooks and queens of the given color,
+/// straight from the attack tables. Pawns and the king are left out, as their moves say little
+/// about how active a position is.
+fn mobility(board: &BitBoard, color: Color) -> i32 {
+    let not_own = !BoardMask(board.occupancy_for(color).0);
+    let knights = board
+        .pieces(Piece::Knight, color)
+        .iter()
+        .map(|sq| ATTACK_TABLES.get_attack_pattern_knight(sq));
+    let bishops = board
+        .pieces(Piece::Bishop, color)
+        .iter()
+        .map(|sq| ATTACK_TABLES.get_attack_pattern_bishop(sq, board.occupancy));
+    let rooks = board
+        .pieces(Piece::Rook { has_moved: true }, color)
+        .iter()
+        .map(|sq| ATTACK_TABLES.get_attack_pattern_rook(sq, board.occupancy));
+    let queens = board
+        .pieces(Piece::Queen, color)
+        .iter()
+        .map(|sq| ATTACK_TABLES.get_attack_pattern_queen(sq, board.occupancy));
+    knights
+        .chain(bishops)
+        .chain(rooks)
+        .chain(queens)
+        .map(|pattern| (pattern & not_own).count_ones() as i32)
+        .sum()
+}
+
+/// scores the pawn structure of the given color: doubled and isolated pawns are penalized, passed
+/// pawns get a bonus growing with how far they have advanced.
+pub fn pawn_structure_score(board: &BitBoard, color: Color, params: &EvalParams) -> i32 {
+    let pawns = board.pieces(Piece::Pawn, color).0;
+    let enemy_pawns = board.pieces(Piece::Pawn, !color).0;
+
+    let doubled: i32 = (0..8)
+        .map(|file| (pawns & file_mask(file)).count_ones().saturating_sub(1) as i32)
+        .sum();
+
+    let mut score = doubled * params.doubled_pawn_penalty;
+    for square in BoardMask(pawns).iter() {
+        let rank = square.as_index() / 8;
+        let file = square.as_index() % 8;
+        if pawns & adjacent_files_mask(file) == 0 {
+            score += params.isolated_pawn_penalty;
+        }
+        // all squares on the ranks in front of the pawn, seen from its color
+        let ahead = match color {
+            Color::White if rank < 7 => u64::MAX << ((rank + 1) * 8),
+            Color::White => 0,
+            Color::Black => (1_u64 << (rank * 8)) - 1,
+        };
+        if enemy_pawns & ahead & (file_mask(file) | adjacent_files_mask(file)) == 0 {
+            let advanced = if color.is_white() { rank - 1 } else { 6 - rank };
+            score += params.passed_pawn_bonus[advanced.min(7)];
+        }
+    }
+    score
+}
+
+/// scores how well the king of the given color is protected: a castled king gets a bonus for the
+/// pawns in front of it, every square next to the king attacked by the enemy is penalized.
+pub fn king_safety(board: &BitBoard, color: Color, params: &EvalParams) -> i32 {
+    let Some(king) = board.king_square(color) else {
+        return 0;
+    };
+    let rank = king.as_index() / 8;
+    let file = king.as_index() % 8;
+    let back_rank = if color.is_white() { 0 } else { 7 };
+
+    let mut score = 0;
+    // a king still in the center hasn't castled and has no shield to speak of
+    if rank == back_rank && !(3..=4).contains(&file) {
+        let pawns = board.pieces(Piece::Pawn, color).0 & (file_mask(file) | adjacent_files_mask(file));
+        for (ahead, bonus) in params.pawn_shield_bonus.iter().enumerate() {
+            let shield_rank = if color.is_white() { rank + 1 + ahead } else { rank - 1 - ahead };
+            score += bonus * (pawns & (0xff << (shield_rank * 8))).count_ones() as i32;
+        }
+    }
+    let zone = ATTACK_TABLES.get_attack_pattern_king(king);
+    score + params.king_zone_attack_penalty * (board.attacks_by(!color) & zone).count_ones() as i32
+}
+
+/// recognizes king and pawn against king endgames with the rule of the square and the key squares
+/// the attacking king has to reach. Returns Some(true) for a draw, Some(false) if the side with the
+/// pawn wins and None if the position isn't KPvK or the rules can't tell.
+pub fn kpk_is_draw(board: &BitBoard, side_to_move: Color) -> Option<bool> {
+    if board.occupancy.0.count_ones() != 3 {
+        return None;
+    }
+    let (pawn, strong) = [Color::White, Color::Black]
+        .into_iter()
+        .find_map(|color| board.pieces(Piece::Pawn, color).iter().next().map(|square| (square, color)))?;
+    let weak_king = board.king_square(!strong)?;
+    let weak_to_move = side_to_move != strong;
+
+    // the defending king has nowhere to go
+    if weak_to_move && (ATTACK_TABLES.get_attack_pattern_king(weak_king) & !board.attacks_by(strong)).0 == 0 {
+        return Some(true);
+    }
+
+    // file and rank seen from the side with the pawn, so the pawn always moves up the board
+    let relative = |square: Square| {
+        let rank = (square.as_index() / 8) as i32;
+        let file = (square.as_index() % 8) as i32;
+        if strong.is_white() { (file, rank) } else { (file, 7 - rank) }
+    };
+    let distance = |(f1, r1): (i32, i32), (f2, r2): (i32, i32)| (f1 - f2).abs().max((r1 - r2).abs());
+    let strong_king = relative(board.king_square(strong)?);
+    let weak_king = relative(weak_king);
+    let pawn = relative(pawn);
+    let promotion = (pawn.0, 7);
+
+    // the defending king takes the pawn
+    if weak_to_move && distance(weak_king, pawn) == 1 && distance(strong_king, pawn) > 1 {
+        return Some(true);
+    }
+    // rule of the square: the defending king is too far away to catch the pawn, which promotes
+    // unless its own king is in the way
+    let moves_to_promote = 7 - pawn.1 - i32::from(pawn.1 == 1);
+    let own_king_in_the_way = strong_king.0 == pawn.0 && strong_king.1 > pawn.1;
+    if !own_king_in_the_way && distance(weak_king, promotion) - i32::from(weak_to_move) > moves_to_promote {
+        return Some(false);
+    }
+    // a defending king in the corner in front of a rook pawn can't be driven out
+    if pawn.0 == 0 || pawn.0 == 7 {
+        let in_corner = (weak_king.0 - pawn.0).abs() <= 1 && weak_king.1 >= 6 && weak_king.1 > pawn.1;
+        return in_corner.then_some(true);
+    }
+    // the pawn promotes once the attacking king stands on a key square next to the pawns file, two
+    // ranks in front of the pawn, or one or two ranks in front once it reached the fifth rank
+    let key_ranks = match pawn.1 {
+        rank if rank <= 3 => Some(rank + 2..=rank + 2),
+        rank if rank <= 5 => Some(rank + 1..=rank + 2),
+        _ => None,
+    };
+    if (strong_king.0 - pawn.0).abs() <= 1 && key_ranks.is_some_and(|ranks| ranks.contains(&strong_king.1)) {
+        return Some(false);
+    }
+    // the defending king blocks the pawn and the attacking king is stuck behind it
+    if weak_king == (pawn.0, pawn.1 + 1) && strong_king.1 <= pawn.1 {
+        return Some(true);
+    }
+    None
+}
+
+/// statically evaluates the position in centipawns from the point of view of the player whos turn
+/// it is, so positive values are good for the side to move.
+pub fn evaluate(game: &Game, params: &EvalParams) -> i32 {
+    let material = material_and_position(&game.board, Color::White, params) - material_and_position(&game.board, Color::Black, params);
+    evaluate_with_material(game, params, material)
+}
+
+/// the same as evaluate, but takes material and piece square bonuses from the running scores of
+/// the board instead of adding up every piece. These are kept with the default values and tables,
+/// so params must use them too, see EvalParams::has_default_material.
+pub(crate) fn evaluate_incremental(game: &Game, params: &EvalParams) -> i32 {
+    debug_assert!(params.has_default_material());
+    evaluate_with_material(game, params, game.board.material() + game.board.placement())
+}
+
+/// evaluate with the material and piece square score of white minus black already summed up
+fn evaluate_with_material(game: &Game, params: &EvalParams, material: i32) -> i32 {
+    if kpk_is_draw(&game.board, game.turn) == Some(true) {
+        return 0;
+    }
+    let king_safety_divisor = if game.is_endgame() { ENDGAME_KING_SAFETY_DIVISOR } else { 1 };
+    let side = |color| {
+        params.mobility_weight * mobility(&game.board, color)
+            + pawn_structure_score(&game.board, color, params)
+            + king_safety(&game.board, color, params) / king_safety_divisor
+    };
+    let score = material + side(Color::White) - side(Color::Black);
+    if game.turn.is_white() { score } else { -score }
+}
+
+impl Game {
+    /// the material of white minus the material of black in centipawns, see piece_value
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(Game::init().material_balance(), 0);
+    /// let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    /// assert_eq!(game.material_balance(), 500);
+    ///```
+    pub fn material_balance(&self) -> i32 {
+        self.board.material()
+    }
+
+    /// static exchange evaluation: the material the player to move wins on the target square of mv
+    /// if both sides keep recapturing there with their least valuable piece, each side stopping
+    /// as soon as going on would lose material. Pieces behind the capturing ones join in once the
+    /// way is free. A negative value marks a capture that loses material.
+    ///```
+    /// use athena_chess::game::*;
+    /// // the knight is defended by the pawn, so taking it with the queen loses the queen
+    /// let game = Game::from_fen("4k3/8/2p5/3n4/8/8/3Q4/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(game.see(&Move::new(Piece::Queen, D2, D5, Some(Piece::Knight))), 320 - 900);
+    ///```
+    pub fn see(&self, mv: &Move) -> i32 {
+        // the king is only allowed to take last, as taking it back ends the exchange
+        let value = |piece: Piece| if piece.is_king() { SEE_KING_VALUE } else { piece_value(piece) };
+        let to = mv.get_to();
+        let mut occupancy = self.board.occupancy.with_square_removed(mv.get_from());
+        // the value of the piece standing on to, which the next capture takes
+        let mut on_square = value(mv.get_promotion().unwrap_or(mv.get_piece()));
+        let mut gain = vec![mv.get_takes().map_or(0, value)];
+        if let Some(promotion) = mv.get_promotion() {
+            gain[0] += value(promotion) - value(Piece::Pawn);
+        }
+
+        let mut side = !self.turn;
+        while let Some((square, piece)) = self.least_valuable_attacker(to, side, occupancy) {
+            gain.push(on_square - gain.last().unwrap());
+            occupancy.remove_square(square);
+            on_square = value(piece);
+            side = !side;
+        }
+        // each side only takes if that is better than stopping
+        while gain.len() > 1 {
+            let last = gain.pop().unwrap();
+            let previous = gain.last_mut().unwrap();
+            *previous = -(-*previous).max(last);
+        }
+        gain[0]
+    }
+
+    /// the square and kind of the least valuable piece of color attacking square, only counting the
+    /// pieces in occupancy
+    fn least_valuable_attacker(&self, square: Square, color: Color, occupancy: Occupancy) -> Option<(Square, Piece)> {
+        let attackers = self.board.attackers_through(square, color, occupancy) & BoardMask(occupancy.0);
+        PIECES.iter().find_map(|&piece| {
+            (attackers & self.board.pieces(piece, color))
+                .iter()
+                .next()
+                .map(|from| (from, piece))
+        })
+    }
+
+    /// whether so few pieces are left that the kings should become active. Pawns and kings don't
+    /// count towards the material.
+    pub fn is_endgame(&self) -> bool {
+        // the knights, bishops, rooks and queens of both sides
+        let material: i32 = [Color::White, Color::Black]
+            .iter()
+            .flat_map(|&color| PIECES[1..5].iter().map(move |&piece| (piece, color)))
+            .map(|(piece, color)| piece_value(piece) * self.board.count(piece, color) as i32)
+            .sum();
+        material <= ENDGAME_MATERIAL
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_start_position_is_balanced() {
+        assert_eq!(evaluate(&Game::init(), &EvalParams::default()), 0);
+    }
+
+    #[test]
+    fn test_material_balance_and_endgame() {
+        let game = Game::init();
+        assert_eq!(game.material_balance(), 0);
+        assert_eq!(game.board.count(Piece::Pawn, Color::White), 8);
+        assert_eq!(game.board.count(Piece::Rook { has_moved: false }, Color::Black), 2);
+        assert!(!game.is_endgame());
+
+        let game = Game::from_fen("4k3/pp1n4/8/8/8/8/PPP5/3RK3 b - - 0 1").unwrap();
+        assert_eq!(game.material_balance(), 100 + 500 - 320);
+        assert!(game.is_endgame());
+        // a queen each is too much material for an endgame
+        let game = Game::from_fen("3qk3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        assert!(!game.is_endgame());
+    }
+
+    #[test]
+    fn test_kpk_won() {
+        // the king in front of the pawn on the sixth rank stands on a key square
+        let game = Game::from_fen("8/4K1k1/4P3/8/8/8/8/8 w - - 0 1").unwrap();
+        assert_eq!(kpk_is_draw(&game.board, Color::White), Some(false));
+        assert_eq!(kpk_is_draw(&game.board, Color::Black), Some(false));
+        // the same for black
+        let game = Game::from_fen("8/8/8/8/8/4p3/4k1K1/8 b - - 0 1").unwrap();
+        assert_eq!(kpk_is_draw(&game.board, Color::Black), Some(false));
+        // the defending king is outside the square of the pawn, but only if the pawn moves first
+        let game = Game::from_fen("8/8/k7/8/8/8/6PK/8 w - - 0 1").unwrap();
+        assert_eq!(kpk_is_draw(&game.board, Color::White), Some(false));
+        assert_eq!(kpk_is_draw(&game.board, Color::Black), None);
+        assert_ne!(evaluate(&game, &EvalParams::default()), 0);
+    }
+
+    #[test]
+    fn test_kpk_drawn() {
+        // the defending king reached the corner in front of the rook pawn
+        let game = Game::from_fen("k7/8/8/P7/1K6/8/8/8 w - - 0 1").unwrap();
+        assert_eq!(kpk_is_draw(&game.board, Color::White), Some(true));
+        assert_eq!(evaluate(&game, &EvalParams::default()), 0);
+        // the defending king blocks the pawn, the attacking king is behind it
+        let game = Game::from_fen("8/8/8/4k3/4P3/4K3/8/8 w - - 0 1").unwrap();
+        assert_eq!(kpk_is_draw(&game.board, Color::White), Some(true));
+        // stalemate, even though the attacking king stands on a key square
+        let game = Game::from_fen("k7/2K5/1P6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(kpk_is_draw(&game.board, Color::Black), Some(true));
+        assert_eq!(kpk_is_draw(&game.board, Color::White), Some(false));
+        // the undefended pawn is lost
+        let game = Game::from_fen("8/8/8/3k4/4P3/8/8/7K b - - 0 1").unwrap();
+        assert_eq!(kpk_is_draw(&game.board, Color::Black), Some(true));
+        assert_eq!(kpk_is_draw(&Game::init().board, Color::White), None);
+    }
+
+    #[test]
+    fn test_broken_pawn_shield_is_worse() {
+        let intact = Game::from_fen("r5k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+        let broken = Game::from_fen("r5k1/5ppp/8/8/8/6P1/5P1P/R5K1 w - - 0 1").unwrap();
+        let missing = Game::from_fen("r5k1/5ppp/8/8/8/8/5P1P/R5K1 w - - 0 1").unwrap();
+        assert_eq!(king_safety(&intact.board, Color::White, &EvalParams::default()), 45);
+        assert!(
+            king_safety(&broken.board, Color::White, &EvalParams::default())
+                < king_safety(&intact.board, Color::White, &EvalParams::default())
+        );
+        assert!(
+            king_safety(&missing.board, Color::White, &EvalParams::default())
+                < king_safety(&broken.board, Color::White, &EvalParams::default())
+        );
+        // the shields are mirrored for black
+        assert_eq!(king_safety(&intact.board, Color::Black, &EvalParams::default()), 45);
+    }
+
+    #[test]
+    fn test_attacked_king_zone() {
+        let safe = Game::from_fen("6k1/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let attacked = Game::from_fen("6k1/8/8/8/8/8/8/3rK3 w - - 0 1").unwrap();
+        assert_eq!(king_safety(&safe.board, Color::White, &EvalParams::default()), 0);
+        // of the squares next to the king, the rook on d1 attacks d2
+        assert_eq!(
+            king_safety(&attacked.board, Color::White, &EvalParams::default()),
+            KING_ZONE_ATTACK_PENALTY
+        );
+    }
+
+    #[test]
+    fn test_rook_up() {
+        let game = Game::from_fen("1nbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQk - 0 1").unwrap();
+        assert!((450..=550).contains(&evaluate(&game, &EvalParams::default())));
+
+        // the same position seen by the player who is missing the rook
+        let game = Game::from_fen("1nbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQk - 0 1").unwrap();
+        assert!((-550..=-450).contains(&evaluate(&game, &EvalParams::default())));
+    }
+
+    #[test]
+    fn test_pawn_value_scales_pawn_margin() {
+        // black is missing the h pawn
+        let game = Game::from_fen("rnbqkbnr/ppppppp1/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let default = EvalParams::default();
+        let mut doubled = EvalParams::default();
+        doubled.piece_values[0] *= 2;
+        let margin = evaluate(&game, &default);
+        let doubled_margin = evaluate(&game, &doubled);
+        assert_eq!(doubled_margin, margin + 100);
+        assert!((1.7..=2.3).contains(&(doubled_margin as f64 / margin as f64)));
+    }
+
+    #[test]
+    fn test_centralized_knight_is_better() {
+        let centralized = Game::from_fen("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1").unwrap();
+        let cornered = Game::from_fen("4k3/8/8/8/8/8/8/N3K3 w - - 0 1").unwrap();
+        assert_eq!(mobility(&centralized.board, Color::White), 8);
+        assert_eq!(mobility(&cornered.board, Color::White), 2);
+        assert!(evaluate(&centralized, &EvalParams::default()) > evaluate(&cornered, &EvalParams::default()));
+
+        // the six extra moves of the centralized knight are worth 60 centipawns on top
+        let without = EvalParams {
+            mobility_weight: 0,
+            ..EvalParams::default()
+        };
+        let with = EvalParams {
+            mobility_weight: 10,
+            ..EvalParams::default()
+        };
+        assert_eq!(
+            evaluate(&centralized, &with) - evaluate(&cornered, &with),
+            evaluate(&centralized, &without) - evaluate(&cornered, &without) + 60
+        );
+    }
+
+    #[test]
+    fn test_start_position_mobility() {
+        // only the knights can move, the bishops, rooks and queens are boxed in
+        let game = Game::init();
+        assert_eq!(mobility(&game.board, Color::White), 4);
+        assert_eq!(mobility(&game.board, Color::Black), 4);
+    }
+
+    #[test]
+    fn test_doubled_pawns() {
+        let game = Game::from_fen("4k3/6pp/8/8/8/2P5/2P5/4K3 w - - 0 1").unwrap();
+        // both c pawns are isolated and passed, the second one on the file is doubled
+        assert_eq!(
+            pawn_structure_score(&game.board, Color::White, &EvalParams::default()),
+            DOUBLED_PAWN_PENALTY + 2 * ISOLATED_PAWN_PENALTY + PASSED_PAWN_BONUS[0] + PASSED_PAWN_BONUS[1]
+        );
+        // the black pawns stand next to each other, so neither is isolated
+        assert_eq!(
+            pawn_structure_score(&game.board, Color::Black, &EvalParams::default()),
+            2 * PASSED_PAWN_BONUS[0]
+        );
+    }
+
+    #[test]
+    fn test_passed_pawn() {
+        // the e pawn is passed, the a pawn is blocked by the pawn on b7 watching a6
+        let game = Game::from_fen("4k3/1p6/8/P3P3/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            pawn_structure_score(&game.board, Color::White, &EvalParams::default()),
+            2 * ISOLATED_PAWN_PENALTY + PASSED_PAWN_BONUS[3]
+        );
+        // a black pawn on the neighbouring file in front of it stops it from being passed
+        let game = Game::from_fen("4k3/3p4/8/4P3/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            pawn_structure_score(&game.board, Color::White, &EvalParams::default()),
+            ISOLATED_PAWN_PENALTY
+        );
+        // pawns behind it don't
+        let game = Game::from_fen("4k3/8/8/4P3/3p4/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            pawn_structure_score(&game.board, Color::White, &EvalParams::default()),
+            ISOLATED_PAWN_PENALTY + PASSED_PAWN_BONUS[3]
+        );
+    }
+
+    #[test]
+    fn test_piece_square_tables_are_mirrored() {
+        assert_eq!(piece_square_value(Piece::Knight, Color::White, B1, &EvalParams::default()), -40);
+        assert_eq!(piece_square_value(Piece::Knight, Color::Black, B8, &EvalParams::default()), -40);
+        assert_eq!(piece_square_value(Piece::Pawn, Color::White, E7, &EvalParams::default()), 50);
+        assert_eq!(piece_square_value(Piece::Pawn, Color::Black, E2, &EvalParams::default()), 50);
+        assert_eq!(
+            piece_square_value(Piece::King { has_moved: true }, Color::Black, G8, &EvalParams::default()),
+            30
+        );
+    }
+
+    #[test]
+    fn test_mirror_symmetry() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "6k1/5ppp/8/8/8/8/5PPP/3R2K1 b - - 0 1",
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 1",
+            "r3k2r/8/8/8/3pP3/8/8/R3K2R b Kq e3 0 1",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            let mirrored = game.mirror();
+            // the score is from the point of view of the side to move, which is swapped as well
+            assert_eq!(
+                evaluate(&game, &EvalParams::default()),
+                evaluate(&mirrored, &EvalParams::default()),
+                "{fen}"
+            );
+            assert_eq!(game.material_balance(), -mirrored.material_balance(), "{fen}");
+            assert_eq!(game.get_available_moves().len(), mirrored.get_available_moves().len(), "{fen}");
+            assert_eq!(game.castling_rights().mirror(), mirrored.castling_rights(), "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_see() {
+        // the rook takes a pawn defended by a pawn
+        let game = Game::from_fen("4k3/2p5/3p4/8/8/8/3R4/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            game.see(&Move::new(Piece::Rook { has_moved: true }, D2, D6, Some(Piece::Pawn))),
+            -400
+        );
+        // a pawn takes an undefended pawn
+        let game = Game::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(game.see(&Move::new(Piece::Pawn, E4, D5, Some(Piece::Pawn))), 100);
+        // the second rook joins in through the first one
+        let game = Game::from_fen("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+        assert_eq!(
+            game.see(&Move::new(Piece::Rook { has_moved: true }, D2, D5, Some(Piece::Pawn))),
+            100
+        );
+        // the king can't take back while the square is still defended
+        let game = Game::from_fen("8/8/8/3pk3/8/8/3R4/3RK3 w - - 0 1").unwrap();
+        assert_eq!(
+            game.see(&Move::new(Piece::Rook { has_moved: true }, D2, D5, Some(Piece::Pawn))),
+            100
+        );
+    }
+
+    #[test]
+    fn test_incremental_scores_match_from_scratch() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let params = EvalParams::default();
+        let assert_in_sync = |game: &Game| {
+            let from_scratch = |color| material_and_position(&game.board, color, &params);
+            assert_eq!(
+                game.board.material() + game.board.placement(),
+                from_scratch(Color::White) - from_scratch(Color::Black),
+                "{}",
+                game.to_fen()
+            );
+            assert_eq!(evaluate_incremental(game, &params), evaluate(game, &params), "{}", game.to_fen());
+        };
+        for seed in 0..10 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut game = Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+            assert_in_sync(&game);
+            while game.result.is_none()
+                && game.move_history().len() < 200
+                && let Some(mv) = game.random_move(&mut rng)
+            {
+                game.execute_move(mv).unwrap();
+                assert_in_sync(&game);
+            }
+            while game.undo_move().is_some() {
+                assert_in_sync(&game);
+            }
+        }
+    }
+}
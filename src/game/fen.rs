@@ -0,0 +1,373 @@
+use super::*;
+
+impl Game {
+    /// creates a game from a position in Forsyth-Edwards Notation. The halfmove clock and the
+    /// fullmove number may be omitted. The castling rights are kept as they are given, kings and
+    /// rooks that can't castle anymore are marked as moved. Positions that can't come up in a game
+    /// are rejected, see validate.
+    ///```
+    /// use athena_chess::game::*;
+    /// let game = Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    /// assert!(!game.is_fifty_move_draw());
+    ///```
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if !(4..=6).contains(&fields.len()) {
+            return Err(FenError::WrongFieldCount { found: fields.len() });
+        }
+
+        let mut game = Self::empty();
+
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::InvalidPlacement { rank: ranks.len() });
+        }
+        // the placement starts with the eighth rank
+        for (i, rank) in ranks.iter().enumerate() {
+            let rank_index = 7 - i;
+            let mut file = 0;
+            for c in rank.chars() {
+                if let Some(empty) = c.to_digit(10) {
+                    file += empty as usize;
+                } else {
+                    let (piece, color) = Piece::from_fen_char(c).ok_or(FenError::InvalidPiece { c })?;
+                    if file > 7 {
+                        return Err(FenError::InvalidPlacement { rank: rank_index + 1 });
+                    }
+                    game.board
+                        .place_piece_on_square(piece, color, Square::try_from(rank_index * 8 + file).unwrap());
+                    file += 1;
+                }
+            }
+            if file != 8 {
+                return Err(FenError::InvalidPlacement { rank: rank_index + 1 });
+            }
+        }
+
+        game.turn = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(FenError::InvalidColor),
+        };
+
+        game.castling = CastlingRights::from_fen(fields[2]).ok_or(FenError::InvalidCastling)?;
+        game.apply_castling_rights();
+
+        if fields[3] != "-" {
+            let valid = fields[3].len() == 2
+                && matches!(fields[3].as_bytes()[0], b'a'..=b'h')
+                && matches!((fields[3].as_bytes()[1], game.turn), (b'6', Color::White) | (b'3', Color::Black));
+            if !valid {
+                return Err(FenError::InvalidEnPassant);
+            }
+            game.en_passant = Some(fields[3].parse().map_err(|_| FenError::InvalidEnPassant)?);
+            // the pawn that just moved two squares has to be on the board
+            if game.en_passant_square().is_none() {
+                return Err(FenError::InvalidEnPassant);
+            }
+        }
+
+        if let Some(clock) = fields.get(4) {
+            game.halfmove_clock = clock.parse().map_err(|_| FenError::InvalidCounter)?;
+        }
+        if let Some(fullmove) = fields.get(5) {
+            // some programs write 0, the count starts at 1
+            game.start_fullmove = fullmove.parse::<u32>().map_err(|_| FenError::InvalidCounter)?.max(1);
+        }
+
+        game.validate().map_err(|e| FenError::IllegalPosition { e })?;
+        Ok(game)
+    }
+
+    /// writes the position in Forsyth-Edwards Notation, the reverse of from_fen
+    ///```
+    /// use athena_chess::game::*;
+    /// let fen = "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 3 20";
+    /// assert_eq!(Game::from_fen(fen).unwrap().to_fen(), fen);
+    /// assert_eq!(Game::init().to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    ///```
+    pub fn to_fen(&self) -> String {
+        let mut placement = Vec::new();
+        for rank in Rank::all().rev() {
+            let mut row = String::new();
+            let mut empty = 0;
+            for square in Square::iter_rank(rank) {
+                match self.board.get_piece_on_square(square) {
+                    Some((piece, color)) => {
+                        if empty > 0 {
+                            row.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        row.push(piece.to_fen_char(*color));
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                row.push_str(&empty.to_string());
+            }
+            placement.push(row);
+        }
+        let en_passant = self.en_passant_square().map_or("-".to_string(), |square| square.to_string());
+        format!(
+            "{} {} {} {} {} {}",
+            placement.join("/"),
+            if self.turn.is_white() { "w" } else { "b" },
+            self.castling_rights(),
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number()
+        )
+    }
+
+    /// the board as text, see to_ascii, followed by a line with the rest of what a fen holds: the
+    /// player to move, the castling rights, the en passant square and the move number.
+    ///```
+    /// use athena_chess::game::*;
+    /// let diagram = Game::init().diagram();
+    /// assert!(diagram.ends_with("  a b c d e f g h\nWhite to move, castling KQkq, en passant -, move 1\n"));
+    ///```
+    pub fn diagram(&self) -> String {
+        let en_passant = self.en_passant_square().map_or("-".to_string(), |square| square.to_string());
+        format!(
+            "{}{} to move, castling {}, en passant {}, move {}\n",
+            self.to_ascii(),
+            self.turn,
+            self.castling_rights(),
+            en_passant,
+            self.fullmove_number()
+        )
+    }
+
+    /// the number of the current move, going up after every move of black. It counts on from the
+    /// fullmove number of the fen the game was created from, or 1.
+    pub(crate) fn fullmove_number(&self) -> u32 {
+        let plies = self.moves.len() + self.null_moves.len();
+        // the player to move at the start, every ply switched it
+        let black_started = self.turn.is_white() == (plies % 2 == 1);
+        ((plies + usize::from(black_started)) / 2) as u32 + self.start_fullmove
+    }
+
+    /// marks kings and rooks as moved, unless the castling rights still allow them to castle
+    fn apply_castling_rights(&mut self) {
+        for square in self.board.occupancy.iter() {
+            if let Some(&(mut piece, color)) = self.board.get_piece_on_square(square) {
+                let rank = if color.is_white() { Rank::One } else { Rank::Eight };
+                let (king_side, queen_side) = (CastlingRights::kingside(color), CastlingRights::queenside(color));
+                let can_castle = match piece {
+                    Piece::King { .. } => {
+                        square == Square::from_rank_file(rank, File::E)
+                            && (self.castling.contains(king_side) || self.castling.contains(queen_side))
+                    }
+                    Piece::Rook { .. } if square == Square::from_rank_file(rank, File::H) => self.castling.contains(king_side),
+                    Piece::Rook { .. } if square == Square::from_rank_file(rank, File::A) => self.castling.contains(queen_side),
+                    _ => true,
+                };
+                if !can_castle {
+                    // placed again, so the board keeps its masks and hash in sync
+                    piece.make_moved();
+                    self.board.place_piece_on_square(piece, color, square);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_fen_start_position() {
+        let game = Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let start = Game::init();
+        assert_eq!(game.board, start.board);
+        assert_eq!(game.turn, Color::White);
+        assert_eq!(game.halfmove_clock, 0);
+    }
+
+    #[test]
+    fn test_to_fen_round_trip() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 1",
+            "4k3/8/8/8/8/8/8/4K2R b K - 12 1",
+            "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 20",
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+        ] {
+            assert_eq!(Game::from_fen(fen).unwrap().to_fen(), fen);
+        }
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 20").unwrap();
+        for san in ["O-O", "Kd8"] {
+            game.execute_move(game.parse_san_move(san).unwrap()).unwrap();
+        }
+        assert_eq!(game.to_fen(), "r2k3r/8/8/8/8/8/8/R4RK1 w - - 2 21");
+        let mut game = Game::init();
+        for san in ["e4", "c5", "Nf3"] {
+            game.execute_move(game.parse_san_move(san).unwrap()).unwrap();
+        }
+        assert_eq!(game.to_fen(), "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+    }
+
+    #[test]
+    fn test_diagram() {
+        let expected = "\
+8 r n b q k b n r
+7 p p p p p p p p
+6 . . . . . . . .
+5 . . . . . . . .
+4 . . . . . . . .
+3 . . . . . . . .
+2 P P P P P P P P
+1 R N B Q K B N R
+  a b c d e f g h
+White to move, castling KQkq, en passant -, move 1
+";
+        let mut game = Game::init();
+        assert_eq!(game.diagram(), expected);
+
+        for san in ["e4", "Nf6", "Ke2", "d5"] {
+            game.execute_move(game.parse_san_move(san).unwrap()).unwrap();
+        }
+        assert!(game.diagram().ends_with("White to move, castling kq, en passant d6, move 3\n"));
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b Kq - 3 20").unwrap();
+        assert!(game.diagram().ends_with("Black to move, castling Kq, en passant -, move 20\n"));
+        game.execute_move(game.parse_san_move("O-O-O").unwrap()).unwrap();
+        assert!(game.diagram().ends_with("White to move, castling K, en passant -, move 21\n"));
+    }
+
+    #[test]
+    fn test_from_fen_castling_rights() {
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b Kq - 3 20").unwrap();
+        assert_eq!(game.turn, Color::Black);
+        assert_eq!(game.halfmove_clock, 3);
+        assert_eq!(
+            game.board.get_piece_on_square(H1),
+            Some(&(Piece::Rook { has_moved: false }, Color::White))
+        );
+        assert_eq!(
+            game.board.get_piece_on_square(A1),
+            Some(&(Piece::Rook { has_moved: true }, Color::White))
+        );
+        assert_eq!(
+            game.board.get_piece_on_square(E1),
+            Some(&(Piece::King { has_moved: false }, Color::White))
+        );
+        assert_eq!(
+            game.board.get_piece_on_square(H8),
+            Some(&(Piece::Rook { has_moved: true }, Color::Black))
+        );
+        assert_eq!(
+            game.board.get_piece_on_square(A8),
+            Some(&(Piece::Rook { has_moved: false }, Color::Black))
+        );
+
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+        assert_eq!(
+            game.board.get_piece_on_square(E1),
+            Some(&(Piece::King { has_moved: true }, Color::White))
+        );
+        assert_eq!(
+            game.board.get_piece_on_square(E8),
+            Some(&(Piece::King { has_moved: true }, Color::Black))
+        );
+    }
+
+    #[test]
+    fn test_from_fen_optional_counters() {
+        let game = Game::from_fen("8/8/8/8/8/8/8/K6k w - -").unwrap();
+        assert_eq!(game.halfmove_clock, 0);
+    }
+
+    #[test]
+    fn test_from_fen_errors() {
+        assert_eq!(Game::from_fen("8/8/8 w").unwrap_err(), FenError::WrongFieldCount { found: 2 });
+        assert_eq!(
+            Game::from_fen("8/8/8/8/8/8/8 w - - 0 1").unwrap_err(),
+            FenError::InvalidPlacement { rank: 7 }
+        );
+        assert_eq!(
+            Game::from_fen("8/8/8/8/8/8/8/K7k w - - 0 1").unwrap_err(),
+            FenError::InvalidPlacement { rank: 1 }
+        );
+        assert_eq!(
+            Game::from_fen("8/8/8/8/8/8/8/K5xk w - - 0 1").unwrap_err(),
+            FenError::InvalidPiece { c: 'x' }
+        );
+        assert_eq!(Game::from_fen("8/8/8/8/8/8/8/K6k x - - 0 1").unwrap_err(), FenError::InvalidColor);
+        assert_eq!(
+            Game::from_fen("8/8/8/8/8/8/8/K6k w KX - 0 1").unwrap_err(),
+            FenError::InvalidCastling
+        );
+        assert_eq!(
+            Game::from_fen("8/8/8/8/8/8/8/K6k w - e3 0 1").unwrap_err(),
+            FenError::InvalidEnPassant
+        );
+        assert_eq!(Game::from_fen("8/8/8/8/8/8/8/K6k w - - x 1").unwrap_err(), FenError::InvalidCounter);
+    }
+
+    #[test]
+    fn test_from_fen_en_passant_needs_a_pawn_that_just_moved() {
+        // no black pawn on d5
+        assert_eq!(
+            Game::from_fen("4k3/8/8/4P3/8/8/8/4K3 w - d6 0 1").unwrap_err(),
+            FenError::InvalidEnPassant
+        );
+        // d6 is taken by a knight
+        assert_eq!(
+            Game::from_fen("4k3/8/3n4/3pP3/8/8/8/4K3 w - d6 0 1").unwrap_err(),
+            FenError::InvalidEnPassant
+        );
+        // the pawn on d5 can't have come from d7
+        assert_eq!(
+            Game::from_fen("4k3/3b4/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap_err(),
+            FenError::InvalidEnPassant
+        );
+        assert_eq!(
+            Game::from_fen("4k3/8/8/8/4Pp2/8/8/4K3 b - e3 0 1").unwrap().en_passant_square(),
+            Some(E3)
+        );
+    }
+
+    #[test]
+    fn test_from_fen_illegal_positions() {
+        let illegal = |fen| match Game::from_fen(fen) {
+            Err(FenError::IllegalPosition { e }) => e,
+            other => panic!("expected {fen} to be rejected, got {other:?}"),
+        };
+        assert_eq!(
+            illegal("8/8/8/8/8/8/8/K7 w - - 0 1"),
+            PositionError::KingCount {
+                color: Color::Black,
+                count: 0
+            }
+        );
+        assert_eq!(
+            illegal("k7/8/8/8/8/8/8/K5K1 w - - 0 1"),
+            PositionError::KingCount {
+                color: Color::White,
+                count: 2
+            }
+        );
+        assert_eq!(
+            illegal("8/8/8/8/8/8/8/3Kk3 w - - 0 1"),
+            PositionError::AdjacentKings { white: D1, black: E1 }
+        );
+        assert_eq!(
+            illegal("P6k/8/8/8/8/8/8/K7 w - - 0 1"),
+            PositionError::PawnOnBackRank { square: A8 }
+        );
+        assert_eq!(
+            illegal("7k/8/8/8/8/8/8/K2p4 b - - 0 1"),
+            PositionError::PawnOnBackRank { square: D1 }
+        );
+        assert_eq!(
+            illegal("4k3/8/8/8/8/8/8/K3R3 w - - 0 1"),
+            PositionError::OpponentInCheck { color: Color::Black }
+        );
+        // the side to move may be in check
+        assert!(Game::from_fen("4k3/8/8/8/8/8/8/K3R3 b - - 0 1").is_ok());
+    }
+}
@@ -68,4 +68,68 @@ impl BoardMask {
     pub fn add_squares(&mut self, squares: impl IntoIterator<Item = Square>) {
         squares.into_iter().for_each(|sq| self.add_square(sq));
     }
+    /// returns all squares contained in the mask, ordered from a1 to h8
+    pub fn as_squares(&self) -> Vec<Square> {
+        self.iter().collect()
+    }
+    /// iterates over all squares contained in the mask, ordered from a1 to h8, without allocating
+    ///```
+    /// use athena_chess::game::*;
+    /// let mut mask = BoardMask::default();
+    /// mask.add_squares([H8, A1, E4]);
+    /// assert_eq!(mask.iter().collect::<Vec<_>>(), vec![A1, E4, H8]);
+    /// assert_eq!(BoardMask::default().iter().next(), None);
+    ///```
+    pub fn iter(&self) -> BitIter {
+        BitIter(self.0)
+    }
+    /// the squares strictly between a and b on their shared rank, file or diagonal. If the squares
+    /// are not aligned the mask is empty.
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(BoardMask::between(A1, D1).as_squares(), vec![B1, C1]);
+    /// assert_eq!(BoardMask::between(E8, E5).as_squares(), vec![E6, E7]);
+    /// assert_eq!(BoardMask::between(A1, H8).as_squares(), vec![B2, C3, D4, E5, F6, G7]);
+    /// assert_eq!(BoardMask::between(F2, C5).as_squares(), vec![E3, D4]);
+    /// assert_eq!(BoardMask::between(A1, B2), BoardMask::default());
+    /// assert_eq!(BoardMask::between(A1, B3), BoardMask::default());
+    /// assert_eq!(BoardMask::between(E4, E4), BoardMask::default());
+    ///```
+    pub fn between(a: Square, b: Square) -> Self {
+        let (delta_rank, delta_file) = (a.get_delta_rank(b), a.get_delta_file(b));
+        let mut mask = Self::default();
+        if a == b || (delta_rank != 0 && delta_file != 0 && !a.same_diagonal(b)) {
+            return mask;
+        }
+        let step = delta_rank.signum() * 8 + delta_file.signum();
+        let mut index = a.as_u8() as i8 + step;
+        while index != b.as_u8() as i8 {
+            mask.0 |= 1 << index;
+            index += step;
+        }
+        mask
+    }
 }
+
+/// iterator over the set bits of a BoardMask, see BoardMask::iter
+#[derive(Debug, Clone, Copy)]
+pub struct BitIter(u64);
+
+impl Iterator for BitIter {
+    type Item = Square;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+        let square = Square::new(self.0.trailing_zeros() as u8).expect("a u64 has at most 64 bits");
+        // clear the least significant set bit
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.0.count_ones() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for BitIter {}
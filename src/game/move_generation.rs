@@ -0,0 +1,887 @@
+use rand::seq::IndexedRandom;
+
+use super::board::{pawn_attacks, pawn_double_pushes, pawn_single_pushes};
+use super::*;
+
+impl Game {
+    /// returns all legal moves for the player whos turn it is. Positions without a king, f.e. from
+    /// puzzles, are fine too: a side without a king has no king moves and is never in check, so
+    /// the moves are only fully legal if both kings are on the board.
+    ///```
+    /// use athena_chess::game::*;
+    /// let game = Game::init();
+    /// let moves = game.get_available_moves();
+    /// assert_eq!(moves.len(), 20);
+    /// assert!(moves.contains(&Move::new(Piece::Pawn, E2, E4, None)));
+    ///```
+    pub fn get_available_moves(&self) -> Vec<Move> {
+        let mut moves = MoveList::new();
+        self.generate_moves_into(&mut moves);
+        moves.to_vec()
+    }
+
+    /// the legal moves for the player whos turn it is, same as get_available_moves
+    ///```
+    /// use athena_chess::game::*;
+    /// let game = Game::from_fen("7k/8/8/8/8/8/8/R6K w - - 0 1").unwrap();
+    /// assert_eq!(game.legal_moves(), game.get_available_moves());
+    ///```
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.get_available_moves()
+    }
+
+    /// the number of legal moves for the player whos turn it is, without handing out the moves
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(Game::init().legal_move_count(), 20);
+    ///```
+    pub fn legal_move_count(&self) -> usize {
+        let mut moves = MoveList::new();
+        self.generate_moves_into(&mut moves);
+        moves.len()
+    }
+
+    /// a legal move picked uniformly at random, or None if there are no legal moves. Seeding the rng
+    /// makes the choice reproducible.
+    ///```
+    /// use athena_chess::game::*;
+    /// use rand::SeedableRng;
+    /// let game = Game::init();
+    /// let mv = game.random_move(&mut rand::rngs::StdRng::seed_from_u64(7)).unwrap();
+    /// assert!(game.get_available_moves().contains(&mv));
+    ///```
+    pub fn random_move(&self, rng: &mut impl rand::Rng) -> Option<Move> {
+        let mut moves = MoveList::new();
+        self.generate_moves_into(&mut moves);
+        moves.choose(rng).copied()
+    }
+
+    /// like get_available_moves, but writes the moves into buf, which is cleared first. The list
+    /// lives on the stack, so unlike a Vec nothing is allocated for the moves of a position.
+    pub fn generate_moves_into(&self, buf: &mut MoveList) {
+        buf.clear();
+        for from in self.board.occupancy_for(self.turn).iter() {
+            if let Some(&(piece, _)) = self.board.get_piece_on_square(from)
+                && !piece.is_pawn()
+                && !piece.is_knight()
+                && !piece.is_king()
+            {
+                self.add_pseudo_legal_moves_from(from, piece, buf);
+            }
+        }
+        // the pawns all move at once, the knights and the king are found through their masks
+        self.add_pawn_moves(self.board.pieces(Piece::Pawn, self.turn), buf);
+        self.add_knight_moves(self.board.pieces(Piece::Knight, self.turn), buf);
+        if let Some(king) = self.board.king_square(self.turn)
+            && let Some(&(piece, _)) = self.board.get_piece_on_square(king)
+        {
+            self.add_king_moves(piece, king, buf);
+        }
+
+        let pinned = self.pinned_pieces(self.turn);
+        let in_check = self.is_in_check();
+        buf.retain(|mv| self.is_legal_given_pins(mv, pinned, in_check));
+    }
+
+    /// returns the squares of the pieces of color that are pinned to their king by an enemy rook,
+    /// bishop or queen, meaning they may only move along the line between the two.
+    pub fn pinned_pieces(&self, color: Color) -> BoardMask {
+        let mut pinned = BoardMask::default();
+        let Some(king) = self.board.king_square(color) else {
+            return pinned;
+        };
+        let straight = self.board.pieces(Piece::Rook { has_moved: true }, !color) | self.board.pieces(Piece::Queen, !color);
+        let diagonal = self.board.pieces(Piece::Bishop, !color) | self.board.pieces(Piece::Queen, !color);
+        let on_line = |sq: &Square| sq.get_rank() == king.get_rank() || sq.get_file() == king.get_file();
+        let sliders = straight
+            .iter()
+            .filter(on_line)
+            .chain(diagonal.iter().filter(|sq| sq.same_diagonal(king)));
+
+        let own = BoardMask(self.board.occupancy_for(color).0);
+        let all = BoardMask(self.board.occupancy.0);
+        for slider in sliders {
+            let blockers = BoardMask::between(slider, king) & all;
+            if blockers.count_ones() == 1 && (blockers & own).count_ones() == 1 {
+                pinned |= blockers;
+            }
+        }
+        pinned
+    }
+
+    /// checks whether the move attacks the enemy king, either by the moved piece itself or by
+    /// uncovering a line of another piece.
+    pub fn move_gives_check(&self, mv: &Move) -> bool {
+        let mut board = self.board.clone();
+        board.make_move(mv);
+        board
+            .king_square(!self.turn)
+            .is_some_and(|king| board.square_is_controlled_by(king, self.turn))
+    }
+
+    /// like is_legal, but skips applying the move if it can't expose the king: the king isn't in
+    /// check and the piece isn't pinned. King moves only go to squares that aren't attacked. En
+    /// passant removes a second piece from the board and castling moves the rook too, so they are
+    /// always checked.
+    fn is_legal_given_pins(&self, mv: &Move, pinned: BoardMask, in_check: bool) -> bool {
+        let en_passant = mv.is_en_passant(self);
+        let castling = mv.is_castle();
+        let king = mv.get_piece().is_king() && !castling;
+        let can_expose_king = !king && (in_check || pinned.contains(mv.get_from()) || en_passant || castling);
+        !can_expose_king || self.is_legal(mv)
+    }
+
+    /// the moves the piece on from can make by the way it moves, without looking at whether they
+    /// leave the king in check.
+    pub(crate) fn pseudo_legal_moves_from(&self, from: Square, piece: Piece) -> MoveList {
+        let mut moves = MoveList::new();
+        self.add_pseudo_legal_moves_from(from, piece, &mut moves);
+        moves
+    }
+
+    /// like pseudo_legal_moves_from, but pushes the moves onto moves
+    fn add_pseudo_legal_moves_from(&self, from: Square, piece: Piece, moves: &mut MoveList) {
+        let occupancy = self.board.occupancy;
+        match piece {
+            Piece::Pawn => self.add_pawn_moves(BoardMask::default().with_square(from), moves),
+            Piece::Knight => self.add_moves_from_mask(piece, from, ATTACK_TABLES.get_attack_pattern_knight(from), moves),
+            Piece::Bishop => self.add_moves_from_mask(piece, from, ATTACK_TABLES.get_attack_pattern_bishop(from, occupancy), moves),
+            Piece::Rook { .. } => self.add_moves_from_mask(piece, from, ATTACK_TABLES.get_attack_pattern_rook(from, occupancy), moves),
+            Piece::Queen => self.add_moves_from_mask(piece, from, ATTACK_TABLES.get_attack_pattern_queen(from, occupancy), moves),
+            Piece::King { .. } => self.add_king_moves(piece, from, moves),
+        }
+    }
+
+    /// returns the legal moves of the piece on from, f.e. to highlight where a picked up piece can
+    /// go. The moves are empty if the square is empty or the piece can't move, because it is pinned
+    /// or it isn't its turn.
+    pub fn legal_moves_from(&self, from: Square) -> Vec<Move> {
+        match self.board.get_piece_on_square(from) {
+            Some(&(piece, color)) if color == self.turn => self
+                .pseudo_legal_moves_from(from, piece)
+                .iter()
+                .copied()
+                .filter(|mv| self.is_legal(mv))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// checks whether the pseudo legal move would leave the king of the moving player in check,
+    /// by applying it to a copy of the board.
+    pub fn is_legal(&self, mv: &Move) -> bool {
+        let mut board = self.board.clone();
+        board.make_move(mv);
+        board
+            .king_square(self.turn)
+            .is_none_or(|king| !board.square_is_controlled_by(king, !self.turn))
+    }
+
+    /// turns every square in the mask into a move for piece starting at from. Squares occupied by
+    /// our own pieces are skipped, squares occupied by enemy pieces become captures.
+    fn add_moves_from_mask(&self, piece: Piece, from: Square, mask: BoardMask, moves: &mut MoveList) {
+        let empty = !BoardMask(self.board.occupancy.0);
+        let enemies = BoardMask(self.board.occupancy_for(!self.turn).0);
+        moves.extend((mask & empty).iter().map(|to| Move::new(piece, from, to, None)));
+        // only the captures have to look up the piece they take
+        moves.extend(
+            (mask & enemies)
+                .iter()
+                .map(|to| Move::new(piece, from, to, self.board.get_piece_on_square(to).map(|(taken, _)| *taken))),
+        );
+    }
+
+    /// adds the moves of all knights in the mask
+    fn add_knight_moves(&self, knights: BoardMask, moves: &mut MoveList) {
+        for from in knights.iter() {
+            self.add_moves_from_mask(Piece::Knight, from, ATTACK_TABLES.get_attack_pattern_knight(from), moves);
+        }
+    }
+
+    fn add_king_moves(&self, piece: Piece, from: Square, moves: &mut MoveList) {
+        // the king doesn't block the attacks along the line it is moving on
+        let danger = self
+            .board
+            .attacks_through(!self.turn, self.board.occupancy.with_square_removed(from));
+        let targets = ATTACK_TABLES.get_attack_pattern_king(from) & !danger;
+        self.add_moves_from_mask(piece, from, targets, moves);
+        if piece == (Piece::King { has_moved: false }) {
+            self.add_castling_moves(piece, from, moves);
+        }
+    }
+
+    /// adds the castling moves for the unmoved king on from. The king moves two squares towards
+    /// the rook, the rook is moved by execute_move.
+    fn add_castling_moves(&self, king: Piece, from: Square, moves: &mut MoveList) {
+        let rank = if self.turn.is_white() { Rank::One } else { Rank::Eight };
+        // we can't castle out of check or if the king isn't on its starting square
+        if from != Square::from_rank_file(rank, File::E) || self.board.square_is_controlled_by(from, !self.turn) {
+            return;
+        }
+
+        // the king passes through f and g when castling short
+        if self.castling.contains(CastlingRights::kingside(self.turn))
+            && self.castling_path_is_clear(rank, File::H, &[File::F, File::G], &[File::F, File::G])
+        {
+            moves.push(Move::new(king, from, Square::from_rank_file(rank, File::G), None));
+        }
+        // when castling long the b square has to be empty, but it may be attacked
+        if self.castling.contains(CastlingRights::queenside(self.turn))
+            && self.castling_path_is_clear(rank, File::A, &[File::B, File::C, File::D], &[File::C, File::D])
+        {
+            moves.push(Move::new(king, from, Square::from_rank_file(rank, File::C), None));
+        }
+    }
+
+    /// checks that there is an unmoved rook of our color on rook_file, that all squares on the
+    /// empty files are free and that none of the squares on the safe files are controlled by the
+    /// enemy.
+    fn castling_path_is_clear(&self, rank: Rank, rook_file: File, empty: &[File], safe: &[File]) -> bool {
+        self.board
+            .get_piece_on_square(Square::from_rank_file(rank, rook_file))
+            .is_some_and(|(pc, col)| *pc == Piece::Rook { has_moved: false } && *col == self.turn)
+            && empty.iter().all(|f| !self.board.is_occupied(Square::from_rank_file(rank, *f)))
+            && safe
+                .iter()
+                .all(|f| !self.board.square_is_controlled_by(Square::from_rank_file(rank, *f), !self.turn))
+    }
+
+    /// generates the moves of all pawns in the mask at once, by shifting the whole mask instead of
+    /// looking at each pawn on its own.
+    fn add_pawn_moves(&self, pawns: BoardMask, moves: &mut MoveList) {
+        let color = self.turn;
+        let empty = !BoardMask(self.board.occupancy.0);
+        let enemies = BoardMask(self.board.occupancy_for(!color).0);
+        let last_rank = Rank::promotion_rank(color);
+        // the pawns attacking a square stand where a pawn of the other color on it would attack
+        let attackers = |to: Square| pawn_attacks(BoardMask::default().with_square(to), !color) & pawns;
+        let behind = |to: Square, ranks: i8| to.move_on_file(if color.is_white() { -ranks } else { ranks }).unwrap();
+
+        let mut push = |from: Square, to: Square, takes: Option<Piece>| {
+            if to.get_rank() == last_rank {
+                moves.extend(Move::promotions(from, to, takes));
+            } else {
+                moves.push(Move::new(Piece::Pawn, from, to, takes));
+            }
+        };
+        for to in pawn_single_pushes(pawns, empty, color).iter() {
+            push(behind(to, 1), to, None);
+        }
+        for to in pawn_double_pushes(pawns, empty, color).iter() {
+            push(behind(to, 2), to, None);
+        }
+        for to in (pawn_attacks(pawns, color) & enemies).iter() {
+            let takes = self.board.get_piece_on_square(to).map(|(piece, _)| *piece);
+            attackers(to).iter().for_each(|from| push(from, to, takes));
+        }
+        if let Some(to) = self.en_passant_square() {
+            attackers(to).iter().for_each(|from| push(from, to, Some(Piece::Pawn)));
+        }
+    }
+
+    /// the square a pawn can take en passant on, which is the square the pawn that just made a
+    /// double move skipped. It is only handed out if the board backs it up: the square is empty,
+    /// the enemy pawn stands right behind it and the square that pawn came from is empty.
+    pub(crate) fn en_passant_square(&self) -> Option<Square> {
+        let forward: i8 = if self.turn.is_white() { 1 } else { -1 };
+        self.en_passant.filter(|&square| {
+            let pawn = square.move_on_file(-forward).ok();
+            let start = square.move_on_file(forward).ok();
+            !self.board.is_occupied(square)
+                && pawn.and_then(|pawn| self.board.get_piece_on_square(pawn)) == Some(&(Piece::Pawn, !self.turn))
+                && start.is_some_and(|start| !self.board.is_occupied(start))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_start_position_move_count() {
+        let mut game = Game::init();
+        assert_eq!(game.get_available_moves().len(), 20);
+        game.turn = Color::Black;
+        assert_eq!(game.get_available_moves().len(), 20);
+    }
+
+    #[test]
+    fn test_legal_move_count() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+            "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+            "7k/6Q1/6K1/8/8/8/8/8 b - - 0 1",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            assert_eq!(game.legal_move_count(), game.get_available_moves().len(), "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_random_move_is_reproducible() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let game = Game::init();
+        let first = game.random_move(&mut StdRng::seed_from_u64(42)).unwrap();
+        assert_eq!(game.random_move(&mut StdRng::seed_from_u64(42)), Some(first));
+        assert!(game.get_available_moves().contains(&first));
+        // the seed decides the move, not the position alone
+        let picked: Vec<_> = (0..20)
+            .filter_map(|seed| game.random_move(&mut StdRng::seed_from_u64(seed)))
+            .collect();
+        assert!(picked.iter().any(|&mv| mv != first));
+
+        let mated = Game::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(mated.random_move(&mut StdRng::seed_from_u64(42)), None);
+    }
+
+    #[test]
+    fn test_moves_without_kings() {
+        let mut game = Game::empty();
+        game.place(Piece::Knight, Color::White, A1);
+        game.place(Piece::Knight, Color::White, D4);
+        game.place(Piece::Knight, Color::Black, E6);
+        assert!(!game.is_in_check());
+        assert_eq!(game.pinned_pieces(Color::White), BoardMask::default());
+        let moves = game.get_available_moves();
+        assert_eq!(moves.len(), 2 + 8);
+        assert!(moves.contains(&Move::new(Piece::Knight, D4, E6, Some(Piece::Knight))));
+        assert_eq!(game.status(), GameStatus::Ongoing);
+        for mv in moves {
+            assert!(!game.move_gives_check(&mv));
+            game.clone().execute_move(mv).unwrap();
+        }
+        assert!(game.search_best_move(3).is_some());
+    }
+
+    #[test]
+    fn test_king_moves_in_the_open() {
+        let mut game = Game::empty();
+        game.place(Piece::King { has_moved: true }, Color::White, E4);
+        assert_eq!(game.legal_moves_from(E4).len(), 8);
+    }
+
+    #[test]
+    fn test_king_moves_on_the_edge() {
+        let mut game = Game::empty();
+        game.place(Piece::King { has_moved: true }, Color::White, A4);
+        let moves = game.legal_moves_from(A4);
+        assert_eq!(moves.len(), 5);
+        assert!(moves.iter().all(|m| m.get_to().get_file() <= File::B));
+    }
+
+    #[test]
+    fn test_king_moves_friendly_and_enemy_pieces() {
+        let mut game = Game::empty();
+        let king = Piece::King { has_moved: true };
+        game.place(king, Color::White, E4);
+        game.place(Piece::Pawn, Color::White, E5);
+        game.place(Piece::Bishop, Color::Black, D5);
+        let moves = game.legal_moves_from(E4);
+        // the king can't step onto its own pawn or away from the bishop onto f3
+        assert_eq!(moves.len(), 6);
+        assert!(!moves.iter().any(|m| m.get_to() == E5 || m.get_to() == F3));
+        assert!(moves.contains(&Move::new(king, E4, D5, Some(Piece::Bishop))));
+    }
+
+    /// creates a game with only the kings and the rooks on their starting squares
+    fn create_castling_game() -> Game {
+        let mut game = Game::empty();
+        for (sq, col) in [(E1, Color::White), (E8, Color::Black)] {
+            game.place(Piece::King { has_moved: false }, col, sq);
+        }
+        for (sq, col) in [(A1, Color::White), (H1, Color::White), (A8, Color::Black), (H8, Color::Black)] {
+            game.place(Piece::Rook { has_moved: false }, col, sq);
+        }
+        game
+    }
+
+    const KING: Piece = Piece::King { has_moved: false };
+
+    #[test]
+    fn test_castling_available() {
+        let mut game = create_castling_game();
+        let moves = game.legal_moves_from(E1);
+        assert!(moves.contains(&Move::new(KING, E1, G1, None)));
+        assert!(moves.contains(&Move::new(KING, E1, C1, None)));
+
+        game.turn = Color::Black;
+        let moves = game.legal_moves_from(E8);
+        assert!(moves.contains(&Move::new(KING, E8, G8, None)));
+        assert!(moves.contains(&Move::new(KING, E8, C8, None)));
+    }
+
+    #[test]
+    fn test_castling_blocked_path() {
+        let mut game = create_castling_game();
+        game.place(Piece::Knight, Color::White, G1);
+        game.place(Piece::Knight, Color::White, B1);
+        let moves = game.legal_moves_from(E1);
+        assert!(!moves.contains(&Move::new(KING, E1, G1, None)));
+        assert!(!moves.contains(&Move::new(KING, E1, C1, None)));
+    }
+
+    #[test]
+    fn test_castling_rook_already_moved() {
+        let mut game = create_castling_game();
+        game.place(Piece::Rook { has_moved: true }, Color::White, H1);
+        let moves = game.legal_moves_from(E1);
+        assert!(!moves.contains(&Move::new(KING, E1, G1, None)));
+        assert!(moves.contains(&Move::new(KING, E1, C1, None)));
+    }
+
+    #[test]
+    fn test_castling_through_check() {
+        let mut game = create_castling_game();
+        // the rook on f8 controls f1, which the king would pass on the way to g1
+        game.place(Piece::Rook { has_moved: true }, Color::Black, F8);
+        let moves = game.legal_moves_from(E1);
+        assert!(!moves.contains(&Move::new(KING, E1, G1, None)));
+        assert!(moves.contains(&Move::new(KING, E1, C1, None)));
+
+        // b1 being attacked doesn't prevent castling long
+        game.board.remove_piece_from_square(F8);
+        game.place(Piece::Rook { has_moved: true }, Color::Black, B8);
+        assert!(game.legal_moves_from(E1).contains(&Move::new(KING, E1, C1, None)));
+    }
+
+    #[test]
+    fn test_castling_out_of_check() {
+        let mut game = create_castling_game();
+        game.place(Piece::Rook { has_moved: true }, Color::Black, E5);
+        let moves = game.legal_moves_from(E1);
+        assert!(!moves.iter().any(|m| m.get_from().get_delta_file(m.get_to()).abs() == 2));
+    }
+
+    #[test]
+    fn test_generated_castling_can_be_executed() {
+        let game = create_castling_game();
+        for to in [G1, C1] {
+            let mut g = game.clone();
+            assert!(g.execute_move(Move::new(KING, E1, to, None)).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_pinned_knight_cant_move() {
+        let mut game = Game::empty();
+        game.place(Piece::King { has_moved: true }, Color::White, E1);
+        game.place(Piece::Knight, Color::White, E2);
+        game.place(Piece::Rook { has_moved: true }, Color::Black, E8);
+        assert!(game.legal_moves_from(E2).is_empty());
+        assert!(!game.is_legal(&Move::new(Piece::Knight, E2, C3, None)));
+    }
+
+    #[test]
+    fn test_pinned_pieces() {
+        // a knight pinned by a bishop and a rook pinned along the file
+        let game = Game::from_fen("4r2k/8/8/8/1b6/8/3NR3/4K3 w - - 0 1").unwrap();
+        let mut expected = BoardMask::default();
+        expected.add_squares([D2, E2]);
+        assert_eq!(game.pinned_pieces(Color::White), expected);
+        assert!(game.legal_moves_from(D2).is_empty());
+        assert!(game.legal_moves_from(E2).iter().all(|mv| mv.get_to().get_file() == File::E));
+        assert_eq!(game.legal_moves_from(E2).len(), 6);
+
+        // two pieces between the slider and the king, or a friendly slider, don't pin
+        let game = Game::from_fen("4r2k/8/8/4N3/1b6/2P5/3NR3/4K3 w - - 0 1").unwrap();
+        assert_eq!(game.pinned_pieces(Color::White), BoardMask::default());
+        assert_eq!(game.pinned_pieces(Color::Black), BoardMask::default());
+    }
+
+    #[test]
+    fn test_move_gives_check() {
+        let game = Game::from_fen("4k3/8/8/8/4B3/8/8/4R1K1 w - - 0 1").unwrap();
+        // the bishop doesn't attack the king from d5, but uncovers the rook
+        assert!(game.move_gives_check(&Move::new(Piece::Bishop, E4, D5, None)));
+        // from c6 the bishop checks as well
+        assert!(game.move_gives_check(&Move::new(Piece::Bishop, E4, C6, None)));
+        assert!(!game.move_gives_check(&Move::new(Piece::King { has_moved: true }, G1, G2, None)));
+
+        // double check by the knight and the uncovered rook
+        let game = Game::from_fen("4k3/8/8/8/4N3/8/8/4R1K1 w - - 0 1").unwrap();
+        assert!(game.move_gives_check(&Move::new(Piece::Knight, E4, D6, None)));
+        assert!(game.move_gives_check(&Move::new(Piece::Knight, E4, G3, None)));
+    }
+
+    /// the pawn moves of the pawn on from, generated square by square
+    fn reference_pawn_moves(game: &Game, from: Square) -> Vec<Move> {
+        let heading = if game.turn.is_white() { 1 } else { -1 };
+        let start_rank = if game.turn.is_white() { Rank::Two } else { Rank::Seven };
+        let last_rank = Rank::promotion_rank(game.turn);
+        let Ok(forward) = from.move_on_file(heading) else {
+            return Vec::new();
+        };
+
+        let mut targets = Vec::new();
+        if !game.board.is_occupied(forward) {
+            targets.push((forward, None));
+            if from.get_rank() == start_rank && !game.board.is_occupied(forward.move_on_file(heading).unwrap()) {
+                targets.push((forward.move_on_file(heading).unwrap(), None));
+            }
+        }
+        for to in [-1, 1].into_iter().filter_map(|side| forward.move_on_rank(side).ok()) {
+            let takes = match game.board.get_piece_on_square(to) {
+                Some((piece, color)) => (*color != game.turn).then_some(*piece),
+                None => game
+                    .moves
+                    .last()
+                    .is_some_and(|m| {
+                        m.get_piece() == Piece::Pawn
+                            && m.get_from().get_delta_rank(m.get_to()).abs() == 2
+                            && m.get_to().get_file() == to.get_file()
+                            && m.get_to().get_rank() == from.get_rank()
+                    })
+                    .then_some(Piece::Pawn),
+            };
+            targets.extend(takes.map(|takes| (to, Some(takes))));
+        }
+
+        let mut moves = Vec::new();
+        for (to, takes) in targets {
+            if to.get_rank() == last_rank {
+                moves.extend(Move::promotions(from, to, takes));
+            } else {
+                moves.push(Move::new(Piece::Pawn, from, to, takes));
+            }
+        }
+        moves
+    }
+
+    #[test]
+    fn test_pawn_moves_match_reference() {
+        // plays random games and compares the pawn moves in every position
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "4k3/pppppppp/8/8/8/8/PPPPPPPP/4K3 w - - 0 1",
+            "n1n1k3/PPP5/8/8/8/8/ppp5/N1N1K3 w - - 0 1",
+        ] {
+            for _ in 0..20 {
+                let mut game = Game::from_fen(fen).unwrap();
+                for _ in 0..60 {
+                    let pawns = game.board.pieces(Piece::Pawn, game.turn);
+                    let mut bulk = MoveList::new();
+                    game.add_pawn_moves(pawns, &mut bulk);
+                    let reference: Vec<Move> = pawns.iter().flat_map(|from| reference_pawn_moves(&game, from)).collect();
+                    assert_eq!(bulk.len(), reference.len(), "{}", game.to_ascii());
+                    assert!(reference.iter().all(|mv| bulk.contains(mv)), "{}", game.to_ascii());
+
+                    let moves = game.get_available_moves();
+                    if moves.is_empty() {
+                        break;
+                    }
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    game.execute_move(moves[state as usize % moves.len()]).unwrap();
+                }
+            }
+        }
+    }
+
+    /// the knight and king moves as they were generated before the masks were split into quiet
+    /// moves and captures, looking up every target square
+    fn reference_step_moves(game: &Game, piece: Piece, from: Square, pattern: BoardMask) -> Vec<Move> {
+        (pattern & !BoardMask(game.board.occupancy_for(game.turn).0))
+            .iter()
+            .map(|to| Move::new(piece, from, to, game.board.get_piece_on_square(to).map(|(taken, _)| *taken)))
+            .collect()
+    }
+
+    #[cfg(feature = "reference-movegen")]
+    #[test]
+    fn test_legal_moves_match_shakmaty() {
+        use shakmaty::fen::Fen;
+        use shakmaty::{CastlingMode, Chess, EnPassantMode, Position};
+
+        // plays random games and compares all legal moves in every position
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "n1n1k3/PPP5/8/8/8/8/ppp5/N1N1K3 w - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            for _ in 0..50 {
+                let mut game = Game::from_fen(fen).unwrap();
+                let mut reference: Chess = fen.parse::<Fen>().unwrap().into_position(CastlingMode::Standard).unwrap();
+                for _ in 0..100 {
+                    let position = Fen::from_position(&reference, EnPassantMode::Legal);
+                    let moves = game.get_available_moves();
+                    let mut ours: Vec<String> = moves.iter().map(Move::to_uci).collect();
+                    let mut theirs: Vec<String> = reference
+                        .legal_moves()
+                        .iter()
+                        .map(|mv| mv.to_uci(CastlingMode::Standard).to_string())
+                        .collect();
+                    ours.sort();
+                    theirs.sort();
+                    assert_eq!(ours, theirs, "{position}");
+                    if moves.is_empty() {
+                        break;
+                    }
+
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    let mv = moves[state as usize % moves.len()];
+                    game.execute_move(mv).unwrap();
+                    let uci = mv.to_uci();
+                    let played = reference
+                        .legal_moves()
+                        .into_iter()
+                        .find(|mv| mv.to_uci(CastlingMode::Standard).to_string() == uci)
+                        .unwrap();
+                    reference.play_unchecked(played);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_knight_and_king_moves_match_reference() {
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "n3k2n/8/2n5/8/8/5N2/8/N3K2N w - - 0 1",
+        ] {
+            for _ in 0..20 {
+                let mut game = Game::from_fen(fen).unwrap();
+                for _ in 0..60 {
+                    let knights = game.board.pieces(Piece::Knight, game.turn);
+                    let mut bulk = MoveList::new();
+                    game.add_knight_moves(knights, &mut bulk);
+                    let reference: Vec<Move> = knights
+                        .iter()
+                        .flat_map(|from| reference_step_moves(&game, Piece::Knight, from, ATTACK_TABLES.get_attack_pattern_knight(from)))
+                        .collect();
+                    assert_eq!(bulk.len(), reference.len(), "{}", game.to_ascii());
+                    assert!(reference.iter().all(|mv| bulk.contains(mv)), "{}", game.to_ascii());
+
+                    let king = game.board.king_square(game.turn).unwrap();
+                    let piece = Piece::King { has_moved: true };
+                    let mut bulk = MoveList::new();
+                    game.add_king_moves(piece, king, &mut bulk);
+                    let danger = game
+                        .board
+                        .attacks_through(!game.turn, game.board.occupancy.with_square_removed(king));
+                    let reference = reference_step_moves(&game, piece, king, ATTACK_TABLES.get_attack_pattern_king(king) & !danger);
+                    assert_eq!(bulk.len(), reference.len(), "{}", game.to_ascii());
+                    assert!(reference.iter().all(|mv| bulk.contains(mv)), "{}", game.to_ascii());
+
+                    let moves = game.get_available_moves();
+                    if moves.is_empty() {
+                        break;
+                    }
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    game.execute_move(moves[state as usize % moves.len()]).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_legal_moves_from_empty_or_enemy_square() {
+        let game = Game::init();
+        assert!(game.legal_moves_from(E4).is_empty());
+        assert!(game.legal_moves_from(E7).is_empty());
+        assert_eq!(game.legal_moves_from(G1).len(), 2);
+    }
+
+    #[test]
+    fn test_king_must_step_out_of_check() {
+        let mut game = Game::empty();
+        let king = Piece::King { has_moved: true };
+        game.place(king, Color::White, E1);
+        game.place(Piece::Pawn, Color::White, A2);
+        game.place(Piece::Rook { has_moved: true }, Color::Black, E8);
+        let mut moves = game.get_available_moves();
+        moves.sort_by_key(|m| m.get_to());
+        assert_eq!(
+            moves,
+            vec![
+                Move::new(king, E1, D1, None),
+                Move::new(king, E1, F1, None),
+                Move::new(king, E1, D2, None),
+                Move::new(king, E1, F2, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_can_be_blocked_or_captured() {
+        let mut game = Game::empty();
+        game.place(Piece::King { has_moved: true }, Color::White, A1);
+        game.place(Piece::Pawn, Color::White, B2);
+        game.place(Piece::Pawn, Color::White, A2);
+        game.place(Piece::Rook { has_moved: true }, Color::White, C8);
+        game.place(Piece::Knight, Color::White, G3);
+        game.place(Piece::Rook { has_moved: true }, Color::Black, H1);
+        let mut moves = game.get_available_moves();
+        moves.sort_by_key(|m| (m.get_from(), m.get_to()));
+        assert_eq!(
+            moves,
+            vec![
+                Move::new(Piece::Knight, G3, F1, None),
+                Move::new(Piece::Knight, G3, H1, Some(Piece::Rook { has_moved: true })),
+                Move::new(Piece::Rook { has_moved: true }, C8, C1, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_promotion_generation() {
+        let mut game = Game::empty();
+        game.place(Piece::Pawn, Color::White, B7);
+        game.place(Piece::Rook { has_moved: false }, Color::Black, A8);
+        game.place(Piece::Knight, Color::Black, B8);
+        let moves = game.legal_moves_from(B7);
+        assert_eq!(moves, Move::promotions(B7, A8, Some(Piece::Rook { has_moved: false })).to_vec());
+
+        game.turn = Color::Black;
+        game.place(Piece::Pawn, Color::Black, H2);
+        let moves = game.legal_moves_from(H2);
+        assert_eq!(moves, Move::promotions(H2, H1, None).to_vec());
+    }
+
+    #[test]
+    fn test_promotion_ignores_has_moved() {
+        let mut game = Game::empty();
+        game.place(Piece::Pawn, Color::White, B7);
+        game.place(Piece::King { has_moved: true }, Color::White, H1);
+        game.place(Piece::King { has_moved: true }, Color::Black, H8);
+        let rook = Move::promotion(B7, B8, None, Piece::Rook { has_moved: false });
+        assert!(game.legal_moves_from(B7).contains(&rook));
+        game.execute_move(rook).unwrap();
+        assert_eq!(game.piece_at(B8), Some((Piece::Rook { has_moved: true }, Color::White)));
+    }
+
+    #[test]
+    fn test_black_pawn_only_promotes_on_rank_one() {
+        let mut game = Game::with_turn(Color::Black);
+        game.place(Piece::King { has_moved: true }, Color::White, H1);
+        game.place(Piece::King { has_moved: true }, Color::Black, H8);
+        game.place(Piece::Pawn, Color::Black, B2);
+        assert_eq!(game.legal_moves_from(B2), Move::promotions(B2, B1, None).to_vec());
+        // a black pawn can't stand on the eighth rank, but if it does it mustn't promote there
+        game.place(Piece::Pawn, Color::Black, C8);
+        assert_eq!(game.legal_moves_from(C8), vec![Move::new(Piece::Pawn, C8, C7, None)]);
+    }
+
+    #[test]
+    fn test_promotion_capture_is_recorded_as_both() {
+        let mut game = Game::from_fen("rn2k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let mv = game
+            .get_available_moves()
+            .into_iter()
+            .find(|mv| mv.get_to() == A8 && mv.get_promotion() == Some(Piece::Queen))
+            .unwrap();
+        assert_eq!(mv.get_takes(), Some(Piece::Rook { has_moved: true }));
+        game.execute_move(mv).unwrap();
+        let played = *game.moves.last().unwrap();
+        assert_eq!(played.get_takes(), Some(Piece::Rook { has_moved: true }));
+        assert_eq!(played.get_promotion(), Some(Piece::Queen));
+    }
+
+    #[test]
+    fn test_every_promotion_capture_can_be_executed() {
+        let game = Game::from_fen("r6k/1P6/8/8/8/8/8/7K w - - 0 1").unwrap();
+        let rook = Piece::Rook { has_moved: true };
+        let captures: Vec<Move> = game.legal_moves_from(B7).into_iter().filter(|mv| mv.get_to() == A8).collect();
+        assert_eq!(captures, Move::promotions(B7, A8, Some(rook)).to_vec());
+        for mv in captures {
+            let mut g = game.clone();
+            g.execute_move(mv).unwrap();
+            assert_eq!(g.piece_at(A8), Some((mv.get_promotion().unwrap(), Color::White)));
+            assert_eq!(g.board.count(rook, Color::Black), 0);
+            assert_eq!(g.board.count(Piece::Pawn, Color::White), 0);
+        }
+        let mut g = game.clone();
+        g.execute_move(Move::promotion(B7, A8, Some(rook), Piece::Knight)).unwrap();
+        assert_eq!(g.piece_at(A8), Some((Piece::Knight, Color::White)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_castling_cant_capture() {
+        Move::new(Piece::King { has_moved: false }, E1, G1, None).with_capture(Piece::Knight);
+    }
+
+    #[test]
+    fn test_generated_moves_can_be_executed() {
+        let game = Game::init();
+        for mv in game.get_available_moves() {
+            let mut g = game.clone();
+            assert!(g.execute_move(mv).is_ok(), "failed to execute {mv}");
+        }
+    }
+
+    #[test]
+    fn test_en_passant_generation() {
+        let mut game = Game::init();
+        game.execute_move(Move::new(Piece::Pawn, E2, E4, None)).unwrap();
+        game.execute_move(Move::new(Piece::Pawn, A7, A6, None)).unwrap();
+        game.execute_move(Move::new(Piece::Pawn, E4, E5, None)).unwrap();
+        game.execute_move(Move::new(Piece::Pawn, D7, D5, None)).unwrap();
+        assert!(
+            game.legal_moves_from(E5)
+                .contains(&Move::new(Piece::Pawn, E5, D6, Some(Piece::Pawn)))
+        );
+    }
+
+    #[test]
+    fn test_en_passant_from_fen() {
+        let fen = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2";
+        let mut game = Game::from_fen(fen).unwrap();
+        let en_passant = Move::new(Piece::Pawn, E5, D6, Some(Piece::Pawn));
+        assert!(game.legal_moves_from(E5).contains(&en_passant));
+        game.execute_move(en_passant).unwrap();
+        assert_eq!(game.piece_at(D5), None);
+        assert_eq!(game.piece_at(D6), Some((Piece::Pawn, Color::White)));
+
+        // without the square in the fen the pawn can't be taken
+        let game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 2").unwrap();
+        assert!(!game.legal_moves_from(E5).contains(&en_passant));
+    }
+
+    #[test]
+    fn test_en_passant_needs_the_pawn_on_the_board() {
+        let en_passant = Move::new(Piece::Pawn, E5, D6, Some(Piece::Pawn));
+        let mut game = Game::from_fen("4k3/8/8/4P3/8/8/8/4K3 w - - 0 1").unwrap();
+        game.en_passant = Some(D6);
+        assert_eq!(game.en_passant_square(), None);
+        assert!(!game.get_available_moves().contains(&en_passant));
+
+        // a knight on the square is only taken once, and not as a pawn
+        let mut game = Game::from_fen("4k3/8/3n4/3pP3/8/8/8/4K3 w - - 0 1").unwrap();
+        game.en_passant = Some(D6);
+        let takes = game.legal_moves_from(E5);
+        assert_eq!(takes.iter().filter(|mv| mv.get_to() == D6).count(), 1);
+        assert!(takes.contains(&Move::new(Piece::Pawn, E5, D6, Some(Piece::Knight))));
+    }
+
+    #[test]
+    fn test_en_passant_is_restored() {
+        let mut game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap();
+        game.execute_move(Move::new(Piece::King { has_moved: true }, E1, E2, None)).unwrap();
+        assert_eq!(game.en_passant_square(), None);
+        game.undo_move();
+        assert_eq!(game.en_passant_square(), Some(D6));
+        game.make_null_move();
+        assert_eq!(game.en_passant_square(), None);
+        game.undo_null_move();
+        assert_eq!(game.en_passant_square(), Some(D6));
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt::Debug;
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+
+use super::*;
+
+// no position has more than 218 legal moves, the rest is room for pseudo legal ones
+const CAPACITY: usize = 256;
+
+/// the moves of a position, kept in a fixed array instead of on the heap, so generating the moves
+/// of a node doesn't allocate. It derefs to a slice of the moves, which gives it len, iter,
+/// indexing and sorting.
+///```
+/// use athena_chess::game::*;
+/// let mut moves = MoveList::new();
+/// moves.push(Move::new(Piece::Pawn, E2, E4, None));
+/// moves.push(Move::new(Piece::Knight, G1, F3, None));
+/// assert_eq!(moves.len(), 2);
+/// assert_eq!(moves[1], Move::new(Piece::Knight, G1, F3, None));
+/// assert_eq!(moves.iter().filter(|mv| mv.get_piece() == Piece::Pawn).count(), 1);
+///```
+#[derive(Clone)]
+pub struct MoveList {
+    // only the first len moves are initialized. Filling the whole array for every node would cost
+    // more than the allocation the list saves.
+    moves: [MaybeUninit<Move>; CAPACITY],
+    len: usize,
+}
+
+impl MoveList {
+    pub const fn new() -> Self {
+        Self {
+            moves: [MaybeUninit::uninit(); CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// adds the move at the end. Panics if the list is full, which no position gets close to.
+    pub fn push(&mut self, mv: Move) {
+        self.moves[self.len].write(mv);
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// keeps only the moves f returns true for, in their order
+    pub fn retain(&mut self, mut f: impl FnMut(&Move) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len {
+            let mv = self[i];
+            if f(&mv) {
+                self.moves[kept].write(mv);
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the first len moves were written by push and MaybeUninit<Move> has the layout of Move
+        unsafe { std::slice::from_raw_parts(self.moves.as_ptr().cast::<Move>(), self.len) }
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: see deref
+        unsafe { std::slice::from_raw_parts_mut(self.moves.as_mut_ptr().cast::<Move>(), self.len) }
+    }
+}
+
+impl Extend<Move> for MoveList {
+    fn extend<T: IntoIterator<Item = Move>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|mv| self.push(mv));
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Debug for MoveList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_retain_keeps_order() {
+        let mut moves = MoveList::new();
+        moves.extend(Square::iter_rank(Rank::Two).map(|from| Move::new(Piece::Pawn, from, from.move_on_file(1).unwrap(), None)));
+        moves.retain(|mv| matches!(mv.get_from().get_file(), File::A | File::C | File::E | File::G));
+        let files: Vec<File> = moves.iter().map(|mv| mv.get_from().get_file()).collect();
+        assert_eq!(files, [File::A, File::C, File::E, File::G]);
+        moves.clear();
+        assert!(moves.is_empty());
+    }
+
+    #[test]
+    fn test_holds_the_most_moves_of_a_position() {
+        // the position with the most legal moves known, 218 of them
+        let game = Game::from_fen("R6R/3Q4/1Q4Q1/4Q3/2Q4Q/Q4Q2/pp1Q4/kBNN1KB1 w - - 0 1").unwrap();
+        let mut moves = MoveList::new();
+        game.generate_moves_into(&mut moves);
+        assert_eq!(moves.len(), 218);
+        assert_eq!(moves.to_vec(), game.get_available_moves());
+    }
+}
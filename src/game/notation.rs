@@ -0,0 +1,210 @@
+use super::*;
+
+impl Game {
+    /// parses a move in the long algebraic notation used by UCI, like e2e4, e1g1 for castling or
+    /// e7e8q for promotions. The move has to be legal in the current position. The promotion
+    /// letter may also be upper case, leaving it out of a promotion is an error.
+    ///```
+    /// use athena_chess::game::*;
+    /// let game = Game::init();
+    /// let mv = game.parse_uci_move("g1f3").unwrap();
+    /// assert_eq!(mv, Move::new(Piece::Knight, G1, F3, None));
+    ///```
+    pub fn parse_uci_move(&self, s: &str) -> Result<Move, MoveParseError> {
+        if !s.is_ascii() || !(4..=5).contains(&s.len()) {
+            return Err(MoveParseError::InvalidFormat);
+        }
+        let from: Square = s[0..2].parse().map_err(|_| MoveParseError::InvalidFormat)?;
+        let to: Square = s[2..4].parse().map_err(|_| MoveParseError::InvalidFormat)?;
+        let promotion = match s.chars().nth(4) {
+            None => None,
+            Some(c) => Some(match c.to_ascii_lowercase() {
+                'q' => Piece::Queen,
+                'r' => Piece::Rook { has_moved: true },
+                'b' => Piece::Bishop,
+                'n' => Piece::Knight,
+                _ => return Err(MoveParseError::InvalidPromotion { c }),
+            }),
+        };
+
+        // the generated moves already know about captures, castling and en passant
+        let moves = self.get_available_moves();
+        let mut candidates = moves.iter().filter(|mv| mv.get_from() == from && mv.get_to() == to);
+        if let Some(mv) = candidates.clone().find(|mv| mv.get_promotion() == promotion) {
+            return Ok(*mv);
+        }
+        if promotion.is_none() && candidates.any(|mv| mv.is_promotion()) {
+            return Err(MoveParseError::MissingPromotion { from, to });
+        }
+        Err(MoveParseError::NotLegal { from, to })
+    }
+
+    /// formats a move in standard algebraic notation like Nbd7, exd5, O-O or e8=Q+. The move has
+    /// to be legal in the current position, otherwise the disambiguation and check markers are
+    /// meaningless.
+    ///```
+    /// use athena_chess::game::*;
+    /// let game = Game::init();
+    /// assert_eq!(game.move_to_san(&Move::new(Piece::Knight, G1, F3, None)), "Nf3");
+    ///```
+    pub fn move_to_san(&self, mv: &Move) -> String {
+        let piece = mv.get_piece();
+        let from = mv.get_from().to_string();
+        let mut san = if mv.is_castle() {
+            if mv.get_from().get_delta_file(mv.get_to()) > 0 {
+                "O-O".to_string()
+            } else {
+                "O-O-O".to_string()
+            }
+        } else {
+            let mut san = piece.to_string();
+            let takes = mv.is_capture();
+            if piece.is_pawn() {
+                if takes {
+                    san.push_str(&from[0..1]);
+                }
+            } else {
+                // other pieces of the same kind that can reach the same square
+                let rivals: Vec<Square> = self
+                    .get_available_moves()
+                    .into_iter()
+                    .filter(|other| {
+                        other.get_to() == mv.get_to()
+                            && other.get_from() != mv.get_from()
+                            && std::mem::discriminant(&other.get_piece()) == std::mem::discriminant(&piece)
+                    })
+                    .map(|other| other.get_from())
+                    .collect();
+                if !rivals.is_empty() {
+                    if rivals.iter().all(|sq| sq.get_file() != mv.get_from().get_file()) {
+                        san.push_str(&from[0..1]);
+                    } else if rivals.iter().all(|sq| sq.get_rank() != mv.get_from().get_rank()) {
+                        san.push_str(&from[1..2]);
+                    } else {
+                        san.push_str(&from);
+                    }
+                }
+            }
+            if takes {
+                san.push('x');
+            }
+            san.push_str(&mv.get_to().to_string());
+            if let Some(promotion) = mv.get_promotion() {
+                san.push_str(&format!("={promotion}"));
+            }
+            san
+        };
+
+        if self.move_gives_check(mv) {
+            let mut next = self.clone();
+            let mate = next.execute_move(*mv).is_ok_and(|outcome| outcome.is_mate);
+            san.push(if mate { '#' } else { '+' });
+        }
+        san
+    }
+
+    /// parses a move in standard algebraic notation like Nf3, exd5 or O-O. Check markers and
+    /// annotations like + or !? are optional and ignored.
+    pub fn parse_san_move(&self, s: &str) -> Result<Move, MoveParseError> {
+        let san = strip_san_suffix(s).replace('0', "O");
+        if san.is_empty() {
+            return Err(MoveParseError::InvalidFormat);
+        }
+        self.get_available_moves()
+            .into_iter()
+            .find(|mv| strip_san_suffix(&self.move_to_san(mv)) == san)
+            .ok_or(MoveParseError::NoMatchingMove)
+    }
+}
+
+/// removes check markers and annotations from the end of a SAN move
+fn strip_san_suffix(san: &str) -> &str {
+    san.trim().trim_end_matches(['+', '#', '!', '?'])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_uci_move() {
+        let game = Game::init();
+        assert_eq!(game.parse_uci_move("e2e4"), Ok(Move::new(Piece::Pawn, E2, E4, None)));
+        assert_eq!(game.parse_uci_move("e2e5"), Err(MoveParseError::NotLegal { from: E2, to: E5 }));
+        assert_eq!(game.parse_uci_move("e7e5"), Err(MoveParseError::NotLegal { from: E7, to: E5 }));
+        assert_eq!(game.parse_uci_move("e2"), Err(MoveParseError::InvalidFormat));
+        assert_eq!(game.parse_uci_move("e2i4"), Err(MoveParseError::InvalidFormat));
+        assert_eq!(game.parse_uci_move("e2e4k"), Err(MoveParseError::InvalidPromotion { c: 'k' }));
+    }
+
+    #[test]
+    fn test_parse_uci_move_en_passant() {
+        let mut game = Game::init();
+        for mv in ["e2e4", "a7a6", "e4e5", "d7d5"] {
+            game.execute_move(game.parse_uci_move(mv).unwrap()).unwrap();
+        }
+        assert_eq!(game.parse_uci_move("e5d6"), Ok(Move::new(Piece::Pawn, E5, D6, Some(Piece::Pawn))));
+    }
+
+    #[test]
+    fn test_parse_uci_move_castling() {
+        let king = Piece::King { has_moved: false };
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(game.parse_uci_move("e1g1"), Ok(Move::new(king, E1, G1, None)));
+        assert_eq!(game.parse_uci_move("e1c1"), Ok(Move::new(king, E1, C1, None)));
+
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+        assert_eq!(game.parse_uci_move("e8g8"), Ok(Move::new(king, E8, G8, None)));
+        assert_eq!(game.parse_uci_move("e8c8"), Ok(Move::new(king, E8, C8, None)));
+
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+        assert_eq!(game.parse_uci_move("e1g1"), Err(MoveParseError::NotLegal { from: E1, to: G1 }));
+    }
+
+    #[test]
+    fn test_parse_uci_move_promotions() {
+        let game = Game::from_fen("3r3k/4P3/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        let [queen, rook, bishop, knight] = Move::promotions(E7, E8, None);
+        assert_eq!(game.parse_uci_move("e7e8q"), Ok(queen));
+        assert_eq!(game.parse_uci_move("e7e8r"), Ok(rook));
+        assert_eq!(game.parse_uci_move("e7e8b"), Ok(bishop));
+        assert_eq!(game.parse_uci_move("e7e8n"), Ok(knight));
+        assert_eq!(
+            game.parse_uci_move("e7d8q"),
+            Ok(Move::promotions(E7, D8, Some(Piece::Rook { has_moved: false }))[0])
+        );
+    }
+
+    #[test]
+    fn test_parse_uci_move_promotion_letter() {
+        let game = Game::from_fen("3r3k/4P3/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        assert_eq!(game.parse_uci_move("e7e8Q"), Ok(Move::promotions(E7, E8, None)[0]));
+        assert_eq!(game.parse_uci_move("e7e8N"), game.parse_uci_move("e7e8n"));
+        assert_eq!(
+            game.parse_uci_move("e7e8"),
+            Err(MoveParseError::MissingPromotion { from: E7, to: E8 })
+        );
+        assert_eq!(
+            game.parse_uci_move("e7d8"),
+            Err(MoveParseError::MissingPromotion { from: E7, to: D8 })
+        );
+        // a promotion letter on a move that doesn't promote is still rejected
+        assert_eq!(game.parse_uci_move("a1a2q"), Err(MoveParseError::NotLegal { from: A1, to: A2 }));
+        assert_eq!(game.parse_uci_move("e7e8K"), Err(MoveParseError::InvalidPromotion { c: 'K' }));
+    }
+
+    #[test]
+    fn test_move_to_san() {
+        // rooks on a1 and a5 share a file, knights on b1 and f1 share a rank
+        let game = Game::from_fen("1k6/7P/8/R7/8/8/8/RN3N1K w - - 0 1").unwrap();
+        let rook = Piece::Rook { has_moved: true };
+        assert_eq!(game.move_to_san(&Move::new(rook, A1, A3, None)), "R1a3");
+        assert_eq!(game.move_to_san(&Move::new(Piece::Rook { has_moved: false }, A5, A3, None)), "R5a3");
+        assert_eq!(game.move_to_san(&Move::new(Piece::Knight, B1, D2, None)), "Nbd2");
+        assert_eq!(game.move_to_san(&Move::new(Piece::Knight, F1, G3, None)), "Ng3");
+        assert_eq!(game.move_to_san(&Move::promotions(H7, H8, None)[0]), "h8=Q+");
+        assert_eq!(game.parse_san_move("Nbd2"), Ok(Move::new(Piece::Knight, B1, D2, None)));
+        assert_eq!(game.parse_san_move("h8=N"), Ok(Move::promotions(H7, H8, None)[3]));
+        assert_eq!(game.parse_san_move("Nd2"), Err(MoveParseError::NoMatchingMove));
+    }
+}
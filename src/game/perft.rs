@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use super::*;
+
+impl Game {
+    /// counts the leaf nodes of the legal move tree up to the given depth. Used to validate move
+    /// generation against known node counts.
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let mut moves = MoveList::new();
+        self.generate_moves_into(&mut moves);
+        // no need to execute the moves on the last level, we only count them
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        let mut nodes = 0;
+        for &mv in &moves {
+            self.apply_move(mv);
+            nodes += self.perft(depth - 1);
+            self.undo_move();
+        }
+        nodes
+    }
+
+    /// perft with the moves of every node in a Vec on the heap instead of a MoveList, which only
+    /// exists to benchmark the two against each other
+    #[doc(hidden)]
+    pub fn perft_vec(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.get_available_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        let mut nodes = 0;
+        for mv in moves {
+            self.apply_move(mv);
+            nodes += self.perft_vec(depth - 1);
+            self.undo_move();
+        }
+        nodes
+    }
+
+    /// like perft, but the subtrees of the root moves are counted in parallel, each on its own copy
+    /// of the game.
+    ///```
+    /// use athena_chess::game::*;
+    /// assert_eq!(Game::init().perft_parallel(3), 8902);
+    ///```
+    pub fn perft_parallel(&self, depth: u32) -> u64 {
+        if depth <= 1 {
+            return self.clone().perft(depth);
+        }
+        self.get_available_moves()
+            .into_par_iter()
+            .map(|mv| {
+                let mut game = self.clone();
+                game.apply_move(mv);
+                game.perft(depth - 1)
+            })
+            .sum()
+    }
+
+    /// like perft, but reports the node count for every move at the root, which helps finding the
+    /// move where the move generation goes wrong.
+    pub fn perft_divide(&mut self, depth: u32) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        self.get_available_moves()
+            .into_iter()
+            .map(|mv| {
+                self.apply_move(mv);
+                let nodes = self.perft(depth - 1);
+                self.undo_move();
+                (mv, nodes)
+            })
+            .collect()
+    }
+
+    /// counts the distinct zobrist hashes of the positions after each number of moves, from one up
+    /// to depth, walking the whole legal move tree like perft.
+    pub fn perft_hash(&mut self, depth: u32) -> Vec<usize> {
+        let mut seen = vec![HashSet::new(); depth as usize];
+        self.collect_hashes(&mut seen, false);
+        seen.iter().map(HashSet::len).collect()
+    }
+
+    /// like perft_hash, but a position whose hash was already seen after the same number of moves
+    /// isn't walked again, as a transposition table would. This only finds the same hashes if equal
+    /// hashes mean equal positions, so a difference points to a bug in the incremental hash.
+    pub fn perft_hash_with_tt(&mut self, depth: u32) -> Vec<usize> {
+        let mut seen = vec![HashSet::new(); depth as usize];
+        self.collect_hashes(&mut seen, true);
+        seen.iter().map(HashSet::len).collect()
+    }
+
+    /// adds the hashes of the positions after one move to the first set, the ones after two moves
+    /// to the second and so on
+    fn collect_hashes(&mut self, seen: &mut [HashSet<u64>], skip_transpositions: bool) {
+        let Some((here, deeper)) = seen.split_first_mut() else {
+            return;
+        };
+        for mv in self.get_available_moves() {
+            self.apply_move(mv);
+            if here.insert(self.zobrist_hash()) || !skip_transpositions {
+                self.collect_hashes(deeper, skip_transpositions);
+            }
+            self.undo_move();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_perft_divide() {
+        let mut game = Game::init();
+        let divide = game.perft_divide(2);
+        assert_eq!(divide.len(), 20);
+        assert!(divide.iter().all(|(_, nodes)| *nodes == 20));
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), game.perft(2));
+    }
+
+    #[test]
+    fn test_perft_leaves_game_unchanged() {
+        let mut game = Game::init();
+        let before = game.clone();
+        game.perft(3);
+        assert_eq!(game.board, before.board);
+        assert_eq!(game.turn, before.turn);
+        assert!(game.moves.is_empty());
+    }
+
+    #[test]
+    fn test_perft_parallel_matches_perft() {
+        let mut game = Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        for depth in 0..=3 {
+            assert_eq!(game.perft_parallel(depth), game.perft(depth));
+        }
+    }
+
+    #[cfg(feature = "perft")]
+    #[test]
+    fn test_perft_parallel_depth_5() {
+        let mut game = Game::init();
+        assert_eq!(game.perft_parallel(5), 4_865_609);
+        assert_eq!(game.perft(5), 4_865_609);
+    }
+
+    #[test]
+    fn test_perft_hash() {
+        let mut game = Game::init();
+        // after 1. e3 e6 2. d3 and 1. d3 e6 2. e3 the same position is reached
+        assert_eq!(game.perft_hash(2), vec![20, 400]);
+        assert!(game.perft_hash(3)[2] < 8902);
+        assert_eq!(game.perft_hash_with_tt(3), game.perft_hash(3));
+    }
+
+    #[cfg(feature = "perft")]
+    #[test]
+    fn test_perft_hash_with_tt_tactical_positions() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        ] {
+            let mut game = Game::from_fen(fen).unwrap();
+            assert_eq!(game.perft_hash_with_tt(4), game.perft_hash(4), "{fen}");
+        }
+    }
+
+    #[cfg(feature = "perft")]
+    #[test]
+    fn test_perft_start_position() {
+        let mut game = Game::init();
+        assert_eq!(game.perft(1), 20);
+        assert_eq!(game.perft(2), 400);
+        assert_eq!(game.perft(3), 8902);
+        assert_eq!(game.perft(4), 197281);
+    }
+}
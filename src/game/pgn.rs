@@ -0,0 +1,321 @@
+use std::fmt::Write;
+
+use super::*;
+
+// the longest a line of movetext may get in the export format
+const MAX_LINE_LENGTH: usize = 80;
+
+/// the seven tag roster every pgn has to contain. The result tag is derived from the game itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnTags {
+    pub event: String,
+    pub site: String,
+    pub date: String,
+    pub round: String,
+    pub white: String,
+    pub black: String,
+}
+
+impl Default for PgnTags {
+    fn default() -> Self {
+        Self {
+            event: "?".to_string(),
+            site: "?".to_string(),
+            date: "????.??.??".to_string(),
+            round: "?".to_string(),
+            white: "?".to_string(),
+            black: "?".to_string(),
+        }
+    }
+}
+
+impl Game {
+    /// the pgn result token for the current position: 1-0, 0-1, 1/2-1/2 or * if the game is still
+    /// going on
+    pub fn result_token(&self) -> &'static str {
+        match self.outcome() {
+            None => "*",
+            Some(GameResult::WhiteWins) => "1-0",
+            Some(GameResult::BlackWins) => "0-1",
+            Some(GameResult::Draw(_)) => "1/2-1/2",
+        }
+    }
+
+    /// exports the game as pgn, with the moves in standard algebraic notation. A game that doesn't
+    /// start from the initial position gets a SetUp and a FEN tag with the position it started in.
+    pub fn to_pgn(&self, tags: &PgnTags) -> String {
+        let result = self.result_token();
+        let mut start = self.clone();
+        while start.undo_move().is_some() {}
+        let start_fen = start.to_fen();
+        let set_up = start_fen != Game::init().to_fen();
+
+        let mut pgn = String::new();
+        let roster = [
+            ("Event", tags.event.as_str()),
+            ("Site", &tags.site),
+            ("Date", &tags.date),
+            ("Round", &tags.round),
+            ("White", &tags.white),
+            ("Black", &tags.black),
+            ("Result", result),
+        ];
+        let set_up_tags = [("SetUp", "1"), ("FEN", start_fen.as_str())];
+        for (name, value) in roster.into_iter().chain(set_up_tags.into_iter().filter(|_| set_up)) {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(pgn, "[{name} \"{value}\"]").expect("writing to a string can't fail");
+        }
+        pgn.push('\n');
+
+        let mut tokens = movetext_tokens(&start, &self.moves);
+        tokens.push(result.to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            pgn.push_str(&token);
+            line_length += token.len();
+        }
+        pgn.push('\n');
+        pgn
+    }
+
+    /// imports the first game of a pgn. Comments, variations and annotations are skipped, a FEN
+    /// tag is used as the starting position.
+    pub fn from_pgn(pgn: &str) -> Result<Self, PgnError> {
+        let mut game = Game::init();
+        let mut movetext = String::new();
+        for (i, line) in pgn.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                let (name, value) = parse_tag(line).ok_or(PgnError::MalformedTag { line: i + 1 })?;
+                if name == "FEN" {
+                    game = Game::from_fen(&value).map_err(|e| PgnError::InvalidFen { e })?;
+                }
+            } else if !line.starts_with('%') {
+                // the rest of the line after a ; is a comment
+                movetext.push_str(line.split(';').next().unwrap_or_default());
+                movetext.push('\n');
+            }
+        }
+
+        let mut ply = 0;
+        for token in strip_comments_and_variations(&movetext).split_whitespace() {
+            if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token) {
+                break;
+            }
+            if token.starts_with('$') {
+                continue;
+            }
+            // move numbers like 12. or 12... may be glued to the move
+            let token = match token.split_once('.') {
+                Some((number, rest)) if number.chars().all(|c| c.is_ascii_digit()) => rest.trim_start_matches('.'),
+                _ => token,
+            };
+            if token.is_empty() {
+                continue;
+            }
+            ply += 1;
+            let mv = game.parse_san_move(token).map_err(|e| PgnError::UnknownMove { ply, e })?;
+            game.execute_move(mv).expect("parsed moves are legal");
+        }
+        Ok(game)
+    }
+}
+
+/// the moves played from start as numbered SAN, like 1. e4 e5 2. Nf3 Nc6, without the headers of
+/// a pgn. The numbers go on from the fullmove number of start and a list starting with a black
+/// move begins with f.e. 1... The list ends before the first illegal move.
+///```
+/// use athena_chess::game::*;
+/// let game = Game::init();
+/// let moves = [Move::new(Piece::Pawn, E2, E4, None), Move::new(Piece::Pawn, E7, E5, None)];
+/// assert_eq!(format_move_list(&game, &moves), "1. e4 e5");
+///```
+pub fn format_move_list(start: &Game, moves: &[Move]) -> String {
+    movetext_tokens(start, moves).join(" ")
+}
+
+/// the move numbers and moves in SAN of the moves played from start. SAN depends on the position
+/// the move was made in, so the moves are replayed.
+fn movetext_tokens(start: &Game, moves: &[Move]) -> Vec<String> {
+    let mut replay = start.clone();
+    let mut tokens = Vec::new();
+    for (ply, mv) in moves.iter().enumerate() {
+        let number = replay.fullmove_number();
+        let white_moves = replay.turn.is_white();
+        let san = replay.move_to_san(mv);
+        if replay.execute_move(*mv).is_err() {
+            break;
+        }
+        if white_moves {
+            tokens.push(format!("{number}."));
+        } else if ply == 0 {
+            tokens.push(format!("{number}..."));
+        }
+        tokens.push(san);
+    }
+    tokens
+}
+
+/// parses a tag pair like [Event "Casual Game"] into its name and value
+fn parse_tag(line: &str) -> Option<(&str, String)> {
+    let (name, value) = line.strip_prefix('[')?.strip_suffix(']')?.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((name, value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
+
+/// removes {comments} and (variations), which can be nested, from the movetext
+fn strip_comments_and_variations(movetext: &str) -> String {
+    let mut stripped = String::new();
+    let mut in_comment = false;
+    let mut depth = 0;
+    for c in movetext.chars() {
+        match c {
+            '{' if !in_comment => in_comment = true,
+            '}' if in_comment => in_comment = false,
+            '(' if !in_comment => depth += 1,
+            ')' if !in_comment && depth > 0 => depth -= 1,
+            // keep tokens on both sides of a comment apart
+            _ if in_comment || depth > 0 => (),
+            c => stripped.push(c),
+        }
+        if in_comment || depth > 0 {
+            stripped.push(' ');
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const OPERA_GAME: &str = "[Event \"Paris\"]
+[Site \"Paris FRA\"]
+[Date \"1858.??.??\"]
+[Round \"?\"]
+[White \"Paul Morphy\"]
+[Black \"Duke Karl / Count Isouard\"]
+[Result \"1-0\"]
+
+1. e4 e5 2. Nf3 d6 3. d4 Bg4 4. dxe5 Bxf3 5. Qxf3 dxe5 6. Bc4 Nf6 7. Qb3 Qe7 8.
+Nc3 c6 9. Bg5 b5 10. Nxb5 cxb5 11. Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7 Rxd7 14.
+Rd1 Qe6 15. Bxd7+ Nxd7 16. Qb8+ Nxb8 17. Rd8# 1-0
+";
+
+    fn opera_tags() -> PgnTags {
+        PgnTags {
+            event: "Paris".to_string(),
+            site: "Paris FRA".to_string(),
+            date: "1858.??.??".to_string(),
+            round: "?".to_string(),
+            white: "Paul Morphy".to_string(),
+            black: "Duke Karl / Count Isouard".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_pgn_round_trip() {
+        let game = Game::from_pgn(OPERA_GAME).unwrap();
+        assert_eq!(game.moves.len(), 33);
+        assert_eq!(game.status(), GameStatus::Checkmate);
+        assert_eq!(game.to_pgn(&opera_tags()), OPERA_GAME);
+        assert!(game.to_pgn(&opera_tags()).lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+    }
+
+    #[test]
+    fn test_pgn_export_ongoing() {
+        let mut game = Game::init();
+        for mv in ["e2e4", "c7c5"] {
+            game.execute_move(game.parse_uci_move(mv).unwrap()).unwrap();
+        }
+        let pgn = game.to_pgn(&PgnTags::default());
+        assert!(pgn.starts_with("[Event \"?\"]\n"));
+        assert!(pgn.contains("[Result \"*\"]\n\n"));
+        assert!(pgn.ends_with("\n1. e4 c5 *\n"));
+    }
+
+    #[test]
+    fn test_pgn_export_after_null_move() {
+        let mut game = Game::init();
+        for mv in ["e2e4", "e7e5"] {
+            game.execute_move(game.parse_uci_move(mv).unwrap()).unwrap();
+        }
+        game.make_null_move();
+        let pgn = game.to_pgn(&PgnTags::default());
+        assert!(!pgn.contains("FEN"), "{pgn}");
+        assert!(pgn.ends_with("\n1. e4 e5 *\n"), "{pgn}");
+    }
+
+    #[test]
+    fn test_pgn_import_skips_comments_and_variations() {
+        let pgn = "1.e4 {best by test} e5 (1... c5 2. Nf3 (2. c3)) 2. Nf3 $1 ; a comment\n2... Nc6 *";
+        let game = Game::from_pgn(pgn).unwrap();
+        assert_eq!(game.moves.len(), 4);
+        assert_eq!(game.moves[3], Move::new(Piece::Knight, B8, C6, None));
+    }
+
+    #[test]
+    fn test_pgn_import_from_fen() {
+        let pgn = "[FEN \"4k3/8/8/8/8/8/8/4K2R w K - 0 1\"]\n\n1. O-O Kd7 *";
+        let game = Game::from_pgn(pgn).unwrap();
+        assert_eq!(game.moves[0], Move::new(Piece::King { has_moved: false }, E1, G1, None));
+        let exported = game.to_pgn(&PgnTags::default());
+        assert!(exported.contains("[Result \"*\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/4K2R w K - 0 1\"]\n"));
+        assert!(exported.ends_with("\n1. O-O Kd7 *\n"));
+        let reimported = Game::from_pgn(&exported).unwrap();
+        assert_eq!(reimported.moves, game.moves);
+        assert_eq!(reimported.to_fen(), game.to_fen());
+
+        // the move numbers go on from the fen
+        let pgn = "[FEN \"4k3/8/8/8/8/8/8/4K2R b K - 0 20\"]\n\n20... Kd7 21. O-O Ke6 *";
+        let game = Game::from_pgn(pgn).unwrap();
+        let exported = game.to_pgn(&PgnTags::default());
+        assert!(exported.ends_with("\n20... Kd7 21. O-O Ke6 *\n"), "{exported}");
+        assert_eq!(Game::from_pgn(&exported).unwrap().moves, game.moves);
+
+        // the initial position doesn't need the tags
+        assert!(!Game::init().to_pgn(&PgnTags::default()).contains("FEN"));
+    }
+
+    #[test]
+    fn test_format_move_list() {
+        // the ruy lopez
+        let mut game = Game::init();
+        for san in ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4"] {
+            game.execute_move(game.parse_san_move(san).unwrap()).unwrap();
+        }
+        assert_eq!(format_move_list(&Game::init(), &game.moves), "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4");
+        assert_eq!(format_move_list(&Game::init(), &[]), "");
+
+        // starting with black to move
+        let start = Game::from_fen("4k3/8/8/8/8/8/8/4K2R b K - 0 1").unwrap();
+        let moves = [start.parse_san_move("Kd7").unwrap()];
+        assert_eq!(format_move_list(&start, &moves), "1... Kd7");
+        // the list stops at an illegal move
+        let moves = [moves[0], Move::new(Piece::Pawn, E2, E4, None)];
+        assert_eq!(format_move_list(&start, &moves), "1... Kd7");
+    }
+
+    #[test]
+    fn test_pgn_import_errors() {
+        assert_eq!(
+            Game::from_pgn("1. e4 e5 2. Ke3").unwrap_err(),
+            PgnError::UnknownMove {
+                ply: 3,
+                e: MoveParseError::NoMatchingMove
+            }
+        );
+        assert_eq!(
+            Game::from_pgn("[Event \"?\"\n1. e4").unwrap_err(),
+            PgnError::MalformedTag { line: 1 }
+        );
+    }
+}
@@ -0,0 +1,920 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use super::evaluation::{EvalParams, evaluate, evaluate_incremental};
+use super::tablebase::{TablebaseProber, Wdl};
+use super::*;
+
+/// the score of being checkmated right now. Mates further away score closer to zero, so the
+/// search prefers the shortest mate.
+pub const MATE_SCORE: i32 = 1_000_000;
+// larger than any score, but still safe to negate
+const INFINITY: i32 = 2 * MATE_SCORE;
+// scores this close to MATE_SCORE are mates
+const MATE_THRESHOLD: i32 = MATE_SCORE - 1_000;
+// the score of a position the tablebase says is won, below every mate so a mate is still preferred
+const TABLEBASE_WIN_SCORE: i32 = MATE_THRESHOLD - 1_000;
+
+// the deepest iterative deepening will search
+const MAX_DEPTH: u32 = 64;
+// the clock and the stop flag are checked every time this many nodes were visited
+const CHECK_INTERVAL: u64 = 2048;
+
+/// the number of entries of the transposition table used by Game::search_best_move
+pub const DEFAULT_TT_SIZE: usize = 1 << 20;
+// a game on the clock is expected to last this many more moves, when spreading the remaining time
+const MOVES_TO_GO: u32 = 30;
+
+/// when a search with Searcher::search_with_limits stops. Every limit that is set applies, without
+/// any the search goes on until it is stopped through the stop handle or finds a mate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    /// the deepest iteration to search
+    pub depth: Option<u32>,
+    /// the number of nodes to visit at most
+    pub nodes: Option<u64>,
+    /// the exact time to search for, instead of deciding it from the clocks
+    pub movetime: Option<Duration>,
+    /// ignore the clocks and search until stopped
+    pub infinite: bool,
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+}
+
+impl SearchLimits {
+    /// the time the player to move may spend on this move: movetime if set, otherwise a share of
+    /// the time left on their clock plus half their increment. None if the search isn't timed.
+    pub fn time_for(&self, color: Color) -> Option<Duration> {
+        if self.infinite {
+            return None;
+        }
+        if self.movetime.is_some() {
+            return self.movetime;
+        }
+        let (time, increment) = match color {
+            Color::White => (self.wtime, self.winc),
+            Color::Black => (self.btime, self.binc),
+        };
+        time.map(|time| (time / MOVES_TO_GO + increment.unwrap_or_default() / 2).min(time / 2))
+    }
+}
+
+/// the number of moves until the side to move mates, negative if it gets mated, or None if the score
+/// isn't a mate score
+pub fn mate_in(score: i32) -> Option<i32> {
+    match score {
+        s if s > MATE_THRESHOLD => Some((MATE_SCORE - s + 1) / 2),
+        s if s < -MATE_THRESHOLD => Some(-(MATE_SCORE + s) / 2),
+        _ => None,
+    }
+}
+
+/// what the search knows after completing an iteration, see Searcher::search_with_info
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchInfo {
+    pub depth: u32,
+    /// the score of the best move from the point of view of the player to move
+    pub score: i32,
+    /// the number of positions visited since the search started
+    pub nodes: u64,
+    /// the time since the search started
+    pub time: Duration,
+    /// the principal variation, the line both players are expected to play, starting with the
+    /// best move
+    pub pv: Vec<Move>,
+}
+
+/// the totals of a search, see Searcher::search_with_stats. Meant for comparing builds of the engine,
+/// a slower move generation shows up as fewer nodes per second.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchStats {
+    /// the number of positions visited
+    pub nodes: u64,
+    /// the nodes visited per second
+    pub nps: f64,
+    /// the deepest completed iteration
+    pub depth: u32,
+    /// the score of the best move from the point of view of the player to move
+    pub score: i32,
+}
+
+/// how a stored score relates to the real score of the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// the score is exact
+    Exact,
+    /// the search failed high, the real score is at least this
+    Lower,
+    /// the search failed low, the real score is at most this
+    Upper,
+}
+
+/// a position analyzed by the search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TtEntry {
+    pub hash: u64,
+    pub depth: u32,
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub bound: Bound,
+}
+
+/// a fixed size table remembering the results of the search by zobrist hash. A position is stored
+/// at index hash % size and only replaces an entry searched to at most the same depth.
+#[derive(Debug, Clone)]
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+}
+
+impl TranspositionTable {
+    /// creates a table with room for size entries, at least one
+    pub fn new(size: usize) -> Self {
+        Self {
+            entries: vec![None; size.max(1)],
+        }
+    }
+
+    fn index(&self, hash: u64) -> usize {
+        (hash % self.entries.len() as u64) as usize
+    }
+
+    /// returns the entry for the position with the given hash, if it is stored
+    pub fn probe(&self, hash: u64) -> Option<&TtEntry> {
+        self.entries[self.index(hash)].as_ref().filter(|entry| entry.hash == hash)
+    }
+
+    /// stores the entry, unless its slot holds a deeper search
+    pub fn store(&mut self, entry: TtEntry) {
+        let index = self.index(entry.hash);
+        if self.entries[index].is_none_or(|old| old.depth <= entry.depth) {
+            self.entries[index] = Some(entry);
+        }
+    }
+
+    /// follows the best moves stored for the positions from game on, the line the search expects
+    /// both players to play, on a copy of the game. The line ends after max_len moves, at a
+    /// position that isn't stored, at a stored move that isn't legal because its entry was
+    /// overwritten by another position and when a position repeats.
+    pub fn extract_pv(&self, game: &Game, max_len: usize) -> Vec<Move> {
+        let mut game = game.clone();
+        let mut seen = HashSet::from([game.zobrist_hash()]);
+        let mut pv = Vec::new();
+        while pv.len() < max_len {
+            let next = self
+                .probe(game.zobrist_hash())
+                .and_then(|entry| entry.best_move)
+                .filter(|mv| game.get_available_moves().contains(mv));
+            let Some(mv) = next else {
+                break;
+            };
+            game.apply_move(mv);
+            pv.push(mv);
+            if !seen.insert(game.zobrist_hash()) {
+                break;
+            }
+        }
+        pv
+    }
+
+    /// removes all entries, f.e. when starting a new game
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+/// the score of a position the tablebase knows the result of, ply moves away from the root. Wins
+/// closer to the root score higher, like mates do.
+fn tablebase_score(wdl: Wdl, ply: i32) -> i32 {
+    match wdl {
+        Wdl::Win => TABLEBASE_WIN_SCORE - ply,
+        Wdl::Loss => -TABLEBASE_WIN_SCORE + ply,
+        Wdl::BlessedLoss | Wdl::Draw | Wdl::CursedWin => 0,
+    }
+}
+
+/// the score of a checkmate or stalemate for the side to move, ply moves away from the root
+fn terminal_score(result: GameResult, ply: i32) -> i32 {
+    match result {
+        GameResult::Draw(_) => 0,
+        // only the side to move can be mated
+        GameResult::WhiteWins | GameResult::BlackWins => -MATE_SCORE + ply,
+    }
+}
+
+/// mate scores are stored relative to the position instead of the root, so they stay correct when
+/// the position is reached at another ply
+fn score_to_tt(score: i32, ply: i32) -> i32 {
+    match score {
+        s if s > MATE_THRESHOLD => s + ply,
+        s if s < -MATE_THRESHOLD => s - ply,
+        s => s,
+    }
+}
+
+fn score_from_tt(score: i32, ply: i32) -> i32 {
+    match score {
+        s if s > MATE_THRESHOLD => s - ply,
+        s if s < -MATE_THRESHOLD => s + ply,
+        s => s,
+    }
+}
+
+/// the rank of a piece for MVV-LVA ordering, from the pawn as the least to the king as the most
+/// valuable piece
+fn mvv_lva_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 1,
+        Piece::Knight => 2,
+        Piece::Bishop => 3,
+        Piece::Rook { .. } => 4,
+        Piece::Queen => 5,
+        Piece::King { .. } => 6,
+    }
+}
+
+/// how promising a move is: captures are ranked by most valuable victim, least valuable attacker,
+/// promotions by the piece promoted to, quiet moves come last
+fn move_priority(mv: &Move) -> i32 {
+    let capture = mv
+        .get_takes()
+        .map_or(0, |victim| mvv_lva_value(victim) * 16 - mvv_lva_value(mv.get_piece()));
+    let promotion = mv.get_promotion().map_or(0, |promotion| mvv_lva_value(promotion) * 16);
+    capture + promotion
+}
+
+/// sorts the moves so the most promising ones are searched first, see move_priority. Moves of
+/// equal priority keep their order.
+pub fn order_moves(moves: &mut [Move]) {
+    moves.sort_by_key(|mv| std::cmp::Reverse(move_priority(mv)));
+}
+
+/// an alpha-beta search over the legal moves of a game
+#[derive(Debug, Clone)]
+pub struct Searcher {
+    params: EvalParams,
+    // whether params use the default material, so the running scores of the board can be used
+    incremental: bool,
+    tt: Option<TranspositionTable>,
+    // the number of positions visited since the searcher was created
+    nodes: u64,
+    // when set, the search stops as soon as it notices the time is up
+    deadline: Option<Instant>,
+    // can be set from another thread to stop the search
+    stop: Arc<AtomicBool>,
+    // the node count at which the search stops
+    node_limit: Option<u64>,
+    // whether the running search may be stopped, which the first iteration of iterative deepening
+    // and plain calls to search_best_move can't
+    stoppable: bool,
+    // whether the running search was stopped, its result has to be thrown away
+    stopped: bool,
+    // asked about positions with at most the given number of pieces instead of searching them
+    tablebase: Option<(Arc<dyn TablebaseProber>, u32)>,
+}
+
+impl Searcher {
+    /// creates a searcher without a transposition table
+    pub fn new(params: EvalParams) -> Self {
+        Self {
+            params,
+            incremental: params.has_default_material(),
+            tt: None,
+            nodes: 0,
+            deadline: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            node_limit: None,
+            stoppable: false,
+            stopped: false,
+            tablebase: None,
+        }
+    }
+
+    /// adds a transposition table with room for size entries
+    pub fn with_transposition_table(mut self, size: usize) -> Self {
+        self.tt = Some(TranspositionTable::new(size));
+        self
+    }
+
+    /// looks up every position with at most max_pieces pieces, kings included, in the tablebase
+    /// instead of searching it. The root is always searched, so there is a move to return.
+    pub fn with_tablebase(mut self, prober: Arc<dyn TablebaseProber>, max_pieces: u32) -> Self {
+        self.tablebase = Some((prober, max_pieces));
+        self
+    }
+
+    /// the number of positions visited so far
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// a flag that stops a running search_timed or search_with_limits when set, f.e. from another
+    /// thread. The search still returns the best move of the deepest completed iteration.
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    /// searches with increasing depth until max has passed and returns the best move of the deepest
+    /// completed search. The first iteration is always completed, so a move is returned whenever
+    /// there is a legal one.
+    pub fn search_timed(&mut self, game: &mut Game, max: Duration) -> Option<(Move, i32)> {
+        let limits = SearchLimits {
+            movetime: Some(max),
+            ..Default::default()
+        };
+        self.search_with_limits(game, &limits)
+    }
+
+    /// searches with increasing depth until one of the limits is reached and returns the best move
+    /// of the deepest completed search. Like search_timed the first iteration is always completed.
+    pub fn search_with_limits(&mut self, game: &mut Game, limits: &SearchLimits) -> Option<(Move, i32)> {
+        self.search_with_info(game, limits, |_| {})
+    }
+
+    /// like search_with_limits, but reports the result of every completed iteration to report,
+    /// f.e. to show the progress of the search in a gui.
+    pub fn search_with_info(&mut self, game: &mut Game, limits: &SearchLimits, mut report: impl FnMut(SearchInfo)) -> Option<(Move, i32)> {
+        let start = Instant::now();
+        let start_nodes = self.nodes;
+        let deadline = limits.time_for(game.turn).map(|time| Instant::now() + time);
+        let node_limit = limits.nodes.map(|nodes| self.nodes + nodes);
+        let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+        self.stop.store(false, Ordering::Relaxed);
+        self.stopped = false;
+        self.deadline = deadline;
+        self.node_limit = node_limit;
+
+        let mut best = None;
+        for depth in 1..=max_depth {
+            // the first iteration always runs to completion
+            self.stoppable = depth > 1;
+            let result = self.search_best_move(game, depth);
+            // the result of a stopped search is incomplete
+            if self.stopped {
+                break;
+            }
+            best = result;
+            if let Some((mv, score)) = result {
+                report(SearchInfo {
+                    depth,
+                    score,
+                    nodes: self.nodes - start_nodes,
+                    time: start.elapsed(),
+                    pv: self.principal_variation(game, mv, depth),
+                });
+            }
+            // no legal moves or a forced mate found, searching deeper won't change anything
+            if result.is_none_or(|(_, score)| score.abs() > MATE_THRESHOLD)
+                || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+                || node_limit.is_some_and(|limit| self.nodes >= limit)
+            {
+                break;
+            }
+        }
+        self.deadline = None;
+        self.node_limit = None;
+        self.stoppable = false;
+        self.stopped = false;
+        best
+    }
+
+    /// like search_with_limits, but returns the totals of the search along with the best move
+    pub fn search_with_stats(&mut self, game: &mut Game, limits: &SearchLimits) -> Option<(Move, SearchStats)> {
+        let start = Instant::now();
+        let start_nodes = self.nodes;
+        let mut depth = 0;
+        let (mv, score) = self.search_with_info(game, limits, |info| depth = info.depth)?;
+        let nodes = self.nodes - start_nodes;
+        // a very short search could otherwise divide by zero
+        let secs = start.elapsed().as_secs_f64().max(1e-6);
+        let stats = SearchStats {
+            nodes,
+            nps: nodes as f64 / secs,
+            depth,
+            score,
+        };
+        Some((mv, stats))
+    }
+
+    /// checks whether the search has to stop. The node limit is checked on every node, the clock
+    /// and the stop flag only every CHECK_INTERVAL nodes.
+    fn should_stop(&mut self) -> bool {
+        if !self.stoppable {
+            return false;
+        }
+        if self.node_limit.is_some_and(|limit| self.nodes >= limit) {
+            self.stopped = true;
+        }
+        if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            self.stopped |= self.deadline.is_some_and(|deadline| Instant::now() >= deadline) || self.stop.load(Ordering::Relaxed);
+        }
+        self.stopped
+    }
+
+    /// searches depth plies deep and returns the best move with its score from the point of view of
+    /// the player whos turn it is. Returns None if there are no legal moves.
+    pub fn search_best_move(&mut self, game: &mut Game, depth: u32) -> Option<(Move, i32)> {
+        let depth = depth.max(1);
+        let mut moves = game.get_available_moves();
+        order_moves(&mut moves);
+        self.order_tt_move_first(game, &mut moves);
+
+        let mut best: Option<(Move, i32)> = None;
+        let mut alpha = -INFINITY;
+        for mv in moves {
+            game.apply_move(mv);
+            let score = -self.negamax(game, depth - 1, -INFINITY, -alpha, 1);
+            game.undo_move();
+            if self.stopped {
+                return None;
+            }
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((mv, score));
+                alpha = alpha.max(score);
+            }
+        }
+
+        if let (Some(tt), Some((mv, score))) = (self.tt.as_mut(), best) {
+            tt.store(TtEntry {
+                hash: game.zobrist_hash(),
+                depth,
+                best_move: Some(mv),
+                score,
+                bound: Bound::Exact,
+            });
+        }
+        best
+    }
+
+    fn negamax(&mut self, game: &mut Game, depth: u32, mut alpha: i32, mut beta: i32, ply: i32) -> i32 {
+        self.nodes += 1;
+        if self.should_stop() {
+            return 0;
+        }
+        // the root has to return a move even if its position occured before
+        if ply > 0 && game.is_repetition() {
+            return 0;
+        }
+        if ply > 0
+            && let Some(wdl) = self.probe_tablebase(game)
+        {
+            return tablebase_score(wdl, ply);
+        }
+        let alpha_orig = alpha;
+        let hash = game.zobrist_hash();
+
+        if let Some(entry) = self.tt.as_ref().and_then(|tt| tt.probe(hash))
+            && entry.depth >= depth
+        {
+            let score = score_from_tt(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower => alpha = alpha.max(score),
+                Bound::Upper => beta = beta.min(score),
+            }
+            if alpha >= beta {
+                return score;
+            }
+        }
+
+        if depth == 0 {
+            return self.quiescence(game, alpha, beta, ply);
+        }
+        let mut moves = MoveList::new();
+        game.generate_moves_into(&mut moves);
+        order_moves(&mut moves);
+        self.order_tt_move_first(game, &mut moves);
+        if let Some(result) = game.terminal_state(&moves) {
+            return terminal_score(result, ply);
+        }
+
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for &mv in &moves {
+            game.apply_move(mv);
+            let score = -self.negamax(game, depth - 1, -beta, -alpha, ply + 1);
+            game.undo_move();
+            if self.stopped {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if let Some(tt) = self.tt.as_mut() {
+            let bound = if best_score <= alpha_orig {
+                Bound::Upper
+            } else if best_score >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            tt.store(TtEntry {
+                hash,
+                depth,
+                best_move,
+                score: score_to_tt(best_score, ply),
+                bound,
+            });
+        }
+        best_score
+    }
+
+    /// searches only captures until the position is quiet, so the evaluation at the end of the
+    /// search isn't taken in the middle of an exchange. The player to move may also stand pat and
+    /// keep the static evaluation instead of capturing.
+    fn quiescence(&mut self, game: &mut Game, mut alpha: i32, beta: i32, ply: i32) -> i32 {
+        self.nodes += 1;
+        if self.should_stop() {
+            return 0;
+        }
+        let mut moves = MoveList::new();
+        game.generate_moves_into(&mut moves);
+        let terminal = game.terminal_state(&moves);
+        // captures losing material in the exchange are left out, standing pat is expected to be better
+        moves.retain(|mv| mv.is_capture() && game.see(mv) >= 0);
+        order_moves(&mut moves);
+        if let Some(result) = terminal {
+            return terminal_score(result, ply);
+        }
+
+        let stand_pat = if self.incremental {
+            evaluate_incremental(game, &self.params)
+        } else {
+            evaluate(game, &self.params)
+        };
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        for &mv in &moves {
+            game.apply_move(mv);
+            let score = -self.quiescence(game, -beta, -alpha, ply + 1);
+            game.undo_move();
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    /// the line starting with the best move first, taken from the transposition table. Without a
+    /// table only the best move is known.
+    fn principal_variation(&self, game: &Game, first: Move, depth: u32) -> Vec<Move> {
+        match self.tt.as_ref().map(|tt| tt.extract_pv(game, depth as usize)) {
+            Some(pv) if pv.first() == Some(&first) => pv,
+            _ => vec![first],
+        }
+    }
+
+    /// the tablebase result of the position, if it has few enough pieces to be looked up
+    fn probe_tablebase(&self, game: &Game) -> Option<Wdl> {
+        let (prober, max_pieces) = self.tablebase.as_ref()?;
+        if game.board.occupancy.0.count_ones() > *max_pieces {
+            return None;
+        }
+        prober.probe_wdl(game)
+    }
+
+    /// moves the best move found for this position in an earlier search to the front
+    fn order_tt_move_first(&self, game: &Game, moves: &mut [Move]) {
+        let tt_move = self
+            .tt
+            .as_ref()
+            .and_then(|tt| tt.probe(game.zobrist_hash()))
+            .and_then(|entry| entry.best_move);
+        if let Some(i) = tt_move.and_then(|tt_move| moves.iter().position(|mv| *mv == tt_move)) {
+            moves[..=i].rotate_right(1);
+        }
+    }
+}
+
+impl Game {
+    /// searches depth plies deep with the default evaluation and a fresh transposition table and
+    /// returns the best move with its score for the player whos turn it is.
+    pub fn search_best_move(&mut self, depth: u32) -> Option<(Move, i32)> {
+        Searcher::new(EvalParams::default())
+            .with_transposition_table(DEFAULT_TT_SIZE)
+            .search_best_move(self, depth)
+    }
+
+    /// searches with iterative deepening until max has passed, see Searcher::search_timed
+    pub fn search_timed(&mut self, max: Duration) -> Option<(Move, i32)> {
+        Searcher::new(EvalParams::default())
+            .with_transposition_table(DEFAULT_TT_SIZE)
+            .search_timed(self, max)
+    }
+
+    /// searches with iterative deepening until one of the limits is reached, see
+    /// Searcher::search_with_limits
+    pub fn search_with_limits(&mut self, limits: &SearchLimits) -> Option<(Move, i32)> {
+        Searcher::new(EvalParams::default())
+            .with_transposition_table(DEFAULT_TT_SIZE)
+            .search_with_limits(self, limits)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_finds_mate_in_one() {
+        let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let (mv, score) = game.search_best_move(3).unwrap();
+        assert_eq!(mv, Move::new(Piece::Rook { has_moved: true }, A1, A8, None));
+        assert_eq!(score, MATE_SCORE - 1);
+        // the search leaves the game as it was
+        assert_eq!(
+            game.zobrist_hash(),
+            Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap().zobrist_hash()
+        );
+    }
+
+    #[test]
+    fn test_takes_hanging_queen() {
+        let mut game = Game::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let (mv, _) = game.search_best_move(2).unwrap();
+        assert_eq!(mv, Move::new(Piece::Rook { has_moved: false }, D2, D5, Some(Piece::Queen)));
+    }
+
+    #[test]
+    fn test_no_moves() {
+        let mut game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(game.search_best_move(2), None);
+    }
+
+    #[test]
+    fn test_transposition_table_saves_nodes() {
+        let fen = "r3k2r/ppp2ppp/2n5/3q4/8/2N5/PPP2PPP/R2QK2R w KQkq - 0 1";
+        let mut plain = Searcher::new(EvalParams::default());
+        let mut with_tt = Searcher::new(EvalParams::default()).with_transposition_table(1 << 16);
+        let plain_best = plain.search_best_move(&mut Game::from_fen(fen).unwrap(), 4).unwrap();
+        let tt_best = with_tt.search_best_move(&mut Game::from_fen(fen).unwrap(), 4).unwrap();
+        assert_eq!(plain_best, tt_best);
+        assert!(with_tt.nodes() < plain.nodes());
+    }
+
+    #[test]
+    fn test_search_timed_returns_legal_move() {
+        let fen = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+        let mut game = Game::from_fen(fen).unwrap();
+        let (mv, _) = game.search_timed(Duration::ZERO).unwrap();
+        assert!(game.get_available_moves().contains(&mv));
+        assert_eq!(game.zobrist_hash(), Game::from_fen(fen).unwrap().zobrist_hash());
+
+        let start = Instant::now();
+        let (mv, _) = game.search_timed(Duration::from_millis(200)).unwrap();
+        assert!(game.get_available_moves().contains(&mv));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_search_timed_stops_at_mate() {
+        let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let (mv, score) = game.search_timed(Duration::from_secs(60)).unwrap();
+        assert_eq!(mv, Move::new(Piece::Rook { has_moved: true }, A1, A8, None));
+        assert_eq!(score, MATE_SCORE - 1);
+    }
+
+    #[test]
+    fn test_search_limits() {
+        let fen = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+        let mut searcher = Searcher::new(EvalParams::default());
+        let depth_2 = SearchLimits {
+            depth: Some(2),
+            ..Default::default()
+        };
+        let mut game = Game::from_fen(fen).unwrap();
+        let best = searcher.search_with_limits(&mut game, &depth_2);
+        assert_eq!(best, Searcher::new(EvalParams::default()).search_best_move(&mut game, 2));
+
+        // the node limit is checked on every node, so the search stops right at it
+        let mut searcher = Searcher::new(EvalParams::default());
+        let nodes = SearchLimits {
+            nodes: Some(5000),
+            ..Default::default()
+        };
+        let (mv, _) = searcher.search_with_limits(&mut game, &nodes).unwrap();
+        assert!(game.get_available_moves().contains(&mv));
+        assert!(searcher.nodes() <= 5000);
+
+        let infinite = SearchLimits {
+            infinite: true,
+            wtime: Some(Duration::from_millis(1)),
+            ..Default::default()
+        };
+        assert_eq!(infinite.time_for(Color::White), None);
+    }
+
+    #[test]
+    fn test_search_info_pv_is_legal() {
+        let fen = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+        let mut game = Game::from_fen(fen).unwrap();
+        let mut searcher = Searcher::new(EvalParams::default()).with_transposition_table(1 << 16);
+        let limits = SearchLimits {
+            depth: Some(4),
+            ..Default::default()
+        };
+        let mut infos = Vec::new();
+        let (best, score) = searcher.search_with_info(&mut game, &limits, |info| infos.push(info)).unwrap();
+
+        assert_eq!(infos.iter().map(|info| info.depth).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        let last = infos.last().unwrap();
+        assert_eq!((last.pv[0], last.score), (best, score));
+        assert!(last.pv.len() > 1);
+        for info in &infos {
+            assert!(info.pv.len() <= info.depth as usize);
+            let mut replay = game.clone();
+            for mv in &info.pv {
+                replay.apply_move_checked(*mv).unwrap();
+            }
+        }
+        assert!(infos.windows(2).all(|w| w[0].nodes < w[1].nodes));
+        assert_eq!(game.zobrist_hash(), Game::from_fen(fen).unwrap().zobrist_hash());
+    }
+
+    #[test]
+    fn test_search_with_stats() {
+        let mut game = Game::init();
+        let mut searcher = Searcher::new(EvalParams::default()).with_transposition_table(1 << 16);
+        let limits = SearchLimits {
+            depth: Some(4),
+            ..Default::default()
+        };
+        let (_, stats) = searcher.search_with_stats(&mut game, &limits).unwrap();
+        assert_eq!(stats.depth, 4);
+        assert!(stats.nodes > 0);
+        assert_eq!(stats.nodes, searcher.nodes());
+        assert!(stats.nps.is_finite() && stats.nps > 0.0);
+    }
+
+    #[test]
+    fn test_extract_pv_ends_in_mate() {
+        // Ra7 cuts the king off, any rook to the eighth rank mates next
+        let mut game = Game::from_fen("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1").unwrap();
+        let mut searcher = Searcher::new(EvalParams::default()).with_transposition_table(1 << 16);
+        let (_, score) = searcher.search_best_move(&mut game, 4).unwrap();
+        assert_eq!(mate_in(score), Some(2));
+
+        let pv = searcher.tt.as_ref().unwrap().extract_pv(&game, 10);
+        assert_eq!(pv.len(), 3);
+        for mv in pv {
+            game.apply_move_checked(mv).unwrap();
+        }
+        assert_eq!(game.status(), GameStatus::Checkmate);
+        assert_eq!(TranspositionTable::new(16).extract_pv(&game, 10), Vec::new());
+    }
+
+    #[test]
+    fn test_search_finds_perpetual_check() {
+        // black is a queen and two rooks up and threatens Rb1#, only Qd8+ Kh7 Qh4+ Kg8 saves white
+        let mut game = Game::from_fen("6k1/q4pp1/8/5P2/7Q/r7/1r4PP/7K w - - 0 1").unwrap();
+        let mut searcher = Searcher::new(EvalParams::default()).with_transposition_table(1 << 16);
+        let (mv, score) = searcher.search_best_move(&mut game, 5).unwrap();
+        assert_eq!(mv, Move::new(Piece::Queen, H4, D8, None));
+        assert_eq!(score, 0);
+    }
+
+    /// a tablebase that says every position is a draw and counts how often it was asked
+    #[derive(Debug, Default)]
+    struct DrawProber {
+        probes: std::sync::atomic::AtomicUsize,
+    }
+
+    impl TablebaseProber for DrawProber {
+        fn probe_wdl(&self, _game: &Game) -> Option<Wdl> {
+            self.probes.fetch_add(1, Ordering::Relaxed);
+            Some(Wdl::Draw)
+        }
+
+        fn probe_dtz(&self, _game: &Game) -> Option<i32> {
+            Some(0)
+        }
+    }
+
+    #[test]
+    fn test_tablebase_is_probed_with_few_pieces() {
+        // five pieces, which the tablebase covers
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/PP6/3QK3 w - - 0 1").unwrap();
+        let prober = Arc::new(DrawProber::default());
+        let mut searcher = Searcher::new(EvalParams::default()).with_tablebase(prober.clone(), 5);
+        let (_, score) = searcher.search_best_move(&mut game, 2).unwrap();
+        assert_eq!(score, 0);
+        assert!(prober.probes.load(Ordering::Relaxed) > 0);
+
+        // a sixth piece is too many, one move deep nothing can be taken yet
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/PPP5/3QK3 w - - 0 1").unwrap();
+        let prober = Arc::new(DrawProber::default());
+        let mut searcher = Searcher::new(EvalParams::default()).with_tablebase(prober.clone(), 5);
+        let (_, score) = searcher.search_best_move(&mut game, 1).unwrap();
+        assert!(score > 900);
+        assert_eq!(prober.probes.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_mate_in() {
+        assert_eq!(mate_in(MATE_SCORE - 1), Some(1));
+        assert_eq!(mate_in(MATE_SCORE - 3), Some(2));
+        assert_eq!(mate_in(-MATE_SCORE + 2), Some(-1));
+        assert_eq!(mate_in(-MATE_SCORE), Some(0));
+        assert_eq!(mate_in(150), None);
+    }
+
+    #[test]
+    fn test_time_for_clock() {
+        let limits = SearchLimits {
+            wtime: Some(Duration::from_secs(60)),
+            btime: Some(Duration::from_secs(30)),
+            winc: Some(Duration::from_secs(2)),
+            ..Default::default()
+        };
+        assert_eq!(limits.time_for(Color::White), Some(Duration::from_secs(3)));
+        assert_eq!(limits.time_for(Color::Black), Some(Duration::from_secs(1)));
+        let movetime = SearchLimits {
+            movetime: Some(Duration::from_millis(500)),
+            ..limits
+        };
+        assert_eq!(movetime.time_for(Color::Black), Some(Duration::from_millis(500)));
+        assert_eq!(SearchLimits::default().time_for(Color::White), None);
+    }
+
+    #[test]
+    fn test_quiescence_sees_recapture() {
+        // taking the pawn on d5 looks good until c6 takes the queen back
+        let mut game = Game::from_fen("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+        let queen_takes_pawn = Move::new(Piece::Queen, D1, D5, Some(Piece::Pawn));
+        let naive = game
+            .get_available_moves()
+            .into_iter()
+            .max_by_key(|mv| {
+                game.apply_move(*mv);
+                let score = -evaluate(&game, &EvalParams::default());
+                game.undo_move();
+                score
+            })
+            .unwrap();
+        assert_eq!(naive, queen_takes_pawn);
+
+        let (mv, score) = game.search_best_move(1).unwrap();
+        assert_ne!(mv, queen_takes_pawn);
+        assert!(score > 0);
+    }
+
+    #[test]
+    fn test_mvv_lva_ordering() {
+        let quiet = Move::new(Piece::Knight, B1, C3, None);
+        let queen_takes_pawn = Move::new(Piece::Queen, D1, D7, Some(Piece::Pawn));
+        let pawn_takes_queen = Move::new(Piece::Pawn, E4, D5, Some(Piece::Queen));
+        let knight_takes_queen = Move::new(Piece::Knight, F4, D5, Some(Piece::Queen));
+        let promotion = Move::promotions(A7, A8, None)[0];
+        let mut moves = vec![quiet, queen_takes_pawn, knight_takes_queen, promotion, pawn_takes_queen];
+        order_moves(&mut moves);
+        assert_eq!(
+            moves,
+            vec![promotion, pawn_takes_queen, knight_takes_queen, queen_takes_pawn, quiet]
+        );
+    }
+
+    #[test]
+    fn test_transposition_table_replace_by_depth() {
+        let mut tt = TranspositionTable::new(8);
+        let entry = TtEntry {
+            hash: 3,
+            depth: 4,
+            best_move: None,
+            score: 10,
+            bound: Bound::Exact,
+        };
+        tt.store(entry);
+        // a shallower search of a position in the same slot doesn't replace it
+        tt.store(TtEntry {
+            hash: 11,
+            depth: 2,
+            ..entry
+        });
+        assert_eq!(tt.probe(3), Some(&entry));
+        assert_eq!(tt.probe(11), None);
+        tt.store(TtEntry {
+            hash: 11,
+            depth: 5,
+            ..entry
+        });
+        assert_eq!(tt.probe(3), None);
+        assert_eq!(tt.probe(11).map(|e| e.depth), Some(5));
+    }
+}
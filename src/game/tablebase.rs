@@ -0,0 +1,27 @@
+use std::fmt::Debug;
+
+use super::*;
+
+/// the result of a position with perfect play, seen from the side to move. A cursed win is a win
+/// that takes too long for the fifty move rule, a blessed loss the same loss for the other side,
+/// so both are draws in practice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+/// looks up positions with few pieces left in endgame tablebases, like the Syzygy tables. The
+/// searcher asks its prober about every position with at most the number of pieces it was
+/// configured with, see Searcher::with_tablebase.
+pub trait TablebaseProber: Debug + Send + Sync {
+    /// the result of the position with perfect play, None if it isn't in the tables
+    fn probe_wdl(&self, game: &Game) -> Option<Wdl>;
+
+    /// the number of plies to the next capture or pawn move with perfect play, negative if the side
+    /// to move loses. None if the position isn't in the tables.
+    fn probe_dtz(&self, game: &Game) -> Option<i32>;
+}
@@ -0,0 +1,154 @@
+use super::board::piece::{Color, Piece};
+use super::board::square::{File, Square};
+
+/// the keys used to hash positions, see ZobristKeys
+pub static ZOBRIST_KEYS: ZobristKeys = ZobristKeys::generate(0x4174_6865_6e61);
+
+/// random keys for every piece on every square and for the side to move. The hash of a position is
+/// the xor of the keys of everything in it, so it can be updated incrementally with every piece
+/// placed or removed. Unmoved kings and rooks get their own keys, so the castling rights are part
+/// of the hash, and the file of an en passant square is hashed while the pawn can be taken. The keys are generated at compile time from a fixed seed, so hashes are the same
+/// on every run.
+#[derive(Debug)]
+pub struct ZobristKeys {
+    pieces: [[u64; 64]; 16],
+    en_passant: [u64; 8],
+    black_to_move: u64,
+}
+
+impl ZobristKeys {
+    const fn generate(seed: u64) -> Self {
+        let mut state = seed;
+        let mut pieces = [[0; 64]; 16];
+        let mut piece = 0;
+        while piece < 16 {
+            let mut square = 0;
+            while square < 64 {
+                pieces[piece][square] = splitmix64(&mut state);
+                square += 1;
+            }
+            piece += 1;
+        }
+        let mut en_passant = [0; 8];
+        let mut file = 0;
+        while file < 8 {
+            en_passant[file] = splitmix64(&mut state);
+            file += 1;
+        }
+        Self {
+            pieces,
+            en_passant,
+            black_to_move: splitmix64(&mut state),
+        }
+    }
+
+    /// the key for the piece of the given color standing on square
+    pub fn piece(&self, piece: Piece, color: Color, square: Square) -> u64 {
+        let state = match piece {
+            Piece::Pawn => 0,
+            Piece::Knight => 1,
+            Piece::Bishop => 2,
+            Piece::Rook { has_moved: false } => 3,
+            Piece::Rook { has_moved: true } => 4,
+            Piece::Queen => 5,
+            Piece::King { has_moved: false } => 6,
+            Piece::King { has_moved: true } => 7,
+        };
+        self.pieces[state * 2 + color as usize][square.as_index()]
+    }
+
+    /// the key for a pawn that can be taken en passant on file
+    pub fn en_passant(&self, file: File) -> u64 {
+        self.en_passant[file as usize]
+    }
+
+    /// the key xored into the hash when it is blacks turn
+    pub fn black_to_move(&self) -> u64 {
+        self.black_to_move
+    }
+}
+
+/// a small, fast pseudo random number generator that can run at compile time
+const fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::Game;
+
+    /// computes the hash of the game from scratch
+    fn full_hash(game: &Game) -> u64 {
+        let mut hash = if game.turn.is_black() { ZOBRIST_KEYS.black_to_move() } else { 0 };
+        if let Some(file) = game.en_passant_file() {
+            hash ^= ZOBRIST_KEYS.en_passant(file);
+        }
+        for square in Square::all() {
+            if let Some((piece, color)) = game.board.get_piece_on_square(square) {
+                hash ^= ZOBRIST_KEYS.piece(*piece, *color, square);
+            }
+        }
+        hash
+    }
+
+    #[test]
+    fn test_keys_are_distinct() {
+        let mut keys: Vec<u64> = ZOBRIST_KEYS.pieces.iter().flatten().copied().collect();
+        keys.extend(ZOBRIST_KEYS.en_passant);
+        keys.push(ZOBRIST_KEYS.black_to_move);
+        let count = keys.len();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), count);
+    }
+
+    #[test]
+    fn test_incremental_hash_matches_full_hash() {
+        let mut game = Game::init();
+        assert_eq!(game.zobrist_hash(), full_hash(&game));
+        for mv in ["e2e4", "d7d5", "e4d5", "g8f6", "f1b5", "c7c6", "g1f3", "c6b5", "e1g1"] {
+            game.execute_move(game.parse_uci_move(mv).unwrap()).unwrap();
+            assert_eq!(game.zobrist_hash(), full_hash(&game), "after {mv}");
+        }
+        while game.undo_move().is_some() {
+            assert_eq!(game.zobrist_hash(), full_hash(&game));
+        }
+        assert_eq!(game.zobrist_hash(), Game::init().zobrist_hash());
+    }
+
+    #[test]
+    fn test_transpositions_hash_equal() {
+        let mut a = Game::init();
+        let mut b = Game::init();
+        for mv in ["g1f3", "g8f6", "b1c3"] {
+            a.execute_move(a.parse_uci_move(mv).unwrap()).unwrap();
+        }
+        for mv in ["b1c3", "g8f6", "g1f3"] {
+            b.execute_move(b.parse_uci_move(mv).unwrap()).unwrap();
+        }
+        assert_eq!(a.zobrist_hash(), b.zobrist_hash());
+
+        // the same pieces with the other side to move is a different position
+        let white = Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let black = Game::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_ne!(white.zobrist_hash(), black.zobrist_hash());
+
+        // as are the same pieces with different castling rights
+        let castling = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        let no_castling = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1").unwrap();
+        assert_ne!(castling.zobrist_hash(), no_castling.zobrist_hash());
+
+        // an en passant square only counts while a pawn can take on it
+        let en_passant = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        let no_en_passant = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_ne!(en_passant.zobrist_hash(), no_en_passant.zobrist_hash());
+        let no_capturer = Game::from_fen("4k3/8/8/3p4/8/8/8/4K3 w - d6 0 1").unwrap();
+        let no_capturer_no_square = Game::from_fen("4k3/8/8/3p4/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(no_capturer.zobrist_hash(), no_capturer_no_square.zobrist_hash());
+    }
+}
@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use crate::game::search::{SearchInfo, SearchLimits, mate_in};
+pub use error::UciError;
+
+mod error;
+
+/// the depth searched when go comes without any limits
+pub const DEFAULT_DEPTH: u32 = 6;
+
+/// parses the arguments of the uci go command, f.e. "wtime 60000 btime 60000 winc 1000" or
+/// "depth 8", into the limits of the search. Unknown arguments are skipped, as the protocol asks.
+/// Without any limits the search goes DEFAULT_DEPTH plies deep.
+///```
+/// use std::time::Duration;
+/// use athena_chess::uci::parse_go;
+/// let limits = parse_go("movetime 500 nodes 100000").unwrap();
+/// assert_eq!(limits.movetime, Some(Duration::from_millis(500)));
+/// assert_eq!(limits.nodes, Some(100_000));
+///```
+pub fn parse_go(args: &str) -> Result<SearchLimits, UciError> {
+    let mut limits = SearchLimits::default();
+    let mut tokens = args.split_whitespace();
+    while let Some(token) = tokens.next() {
+        let mut value = || -> Result<&str, UciError> { tokens.next().ok_or_else(|| UciError::MissingValue { option: token.to_string() }) };
+        match token {
+            "depth" => limits.depth = Some(parse_number(token, value()?)?),
+            "nodes" => limits.nodes = Some(parse_number(token, value()?)?),
+            "movetime" => limits.movetime = Some(parse_millis(token, value()?)?),
+            "wtime" => limits.wtime = Some(parse_millis(token, value()?)?),
+            "btime" => limits.btime = Some(parse_millis(token, value()?)?),
+            "winc" => limits.winc = Some(parse_millis(token, value()?)?),
+            "binc" => limits.binc = Some(parse_millis(token, value()?)?),
+            "infinite" => limits.infinite = true,
+            _ => {}
+        }
+    }
+    // increments alone don't limit the search
+    let unlimited = limits.depth.is_none()
+        && limits.nodes.is_none()
+        && limits.movetime.is_none()
+        && limits.wtime.is_none()
+        && limits.btime.is_none()
+        && !limits.infinite;
+    if unlimited {
+        limits.depth = Some(DEFAULT_DEPTH);
+    }
+    Ok(limits)
+}
+
+/// formats what the search found after an iteration as an uci info line, f.e.
+/// "info depth 5 score cp 31 nodes 12345 time 40 pv e2e4 e7e5". Mates are given in moves.
+pub fn format_info(info: &SearchInfo) -> String {
+    let score = match mate_in(info.score) {
+        Some(moves) => format!("mate {moves}"),
+        None => format!("cp {}", info.score),
+    };
+    let pv: Vec<String> = info.pv.iter().map(|mv| mv.to_uci()).collect();
+    format!(
+        "info depth {} score {score} nodes {} time {} pv {}",
+        info.depth,
+        info.nodes,
+        info.time.as_millis(),
+        pv.join(" ")
+    )
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, UciError> {
+    value.parse().map_err(|_| UciError::InvalidValue {
+        option: option.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// parses a time in milliseconds. Some guis send negative times when the clock ran out, which are
+/// treated as no time left.
+fn parse_millis(option: &str, value: &str) -> Result<Duration, UciError> {
+    let millis: i64 = parse_number(option, value)?;
+    Ok(Duration::from_millis(millis.max(0) as u64))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_go_limits() {
+        let limits = parse_go("depth 8").unwrap();
+        assert_eq!(limits.depth, Some(8));
+        assert_eq!(limits.nodes, None);
+
+        let limits = parse_go("wtime 60000 btime -20 winc 1000 binc 1000 movestogo 20").unwrap();
+        assert_eq!(limits.wtime, Some(Duration::from_secs(60)));
+        assert_eq!(limits.btime, Some(Duration::ZERO));
+        assert_eq!(limits.winc, Some(Duration::from_secs(1)));
+        assert_eq!(limits.binc, Some(Duration::from_secs(1)));
+        assert_eq!(limits.depth, None);
+
+        let limits = parse_go("infinite").unwrap();
+        assert!(limits.infinite);
+        assert_eq!(limits.depth, None);
+
+        let limits = parse_go("nodes 5000 movetime 250").unwrap();
+        assert_eq!(limits.nodes, Some(5000));
+        assert_eq!(limits.movetime, Some(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn test_parse_go_defaults_to_fixed_depth() {
+        let limits = parse_go("").unwrap();
+        assert_eq!(
+            limits,
+            SearchLimits {
+                depth: Some(DEFAULT_DEPTH),
+                ..Default::default()
+            }
+        );
+        // ponder alone doesn't limit the search
+        assert_eq!(parse_go("ponder").unwrap().depth, Some(DEFAULT_DEPTH));
+    }
+
+    #[test]
+    fn test_format_info() {
+        use crate::game::search::MATE_SCORE;
+        use crate::game::*;
+
+        let mut info = SearchInfo {
+            depth: 3,
+            score: -25,
+            nodes: 1234,
+            time: Duration::from_millis(56),
+            pv: vec![Move::new(Piece::Pawn, E2, E4, None), Move::new(Piece::Pawn, E7, E5, None)],
+        };
+        assert_eq!(format_info(&info), "info depth 3 score cp -25 nodes 1234 time 56 pv e2e4 e7e5");
+        info.score = MATE_SCORE - 3;
+        assert!(format_info(&info).contains("score mate 2 "));
+    }
+
+    #[test]
+    fn test_parse_go_errors() {
+        assert_eq!(
+            parse_go("depth").unwrap_err(),
+            UciError::MissingValue {
+                option: "depth".to_string()
+            }
+        );
+        assert_eq!(
+            parse_go("movetime soon").unwrap_err(),
+            UciError::InvalidValue {
+                option: "movetime".to_string(),
+                value: "soon".to_string()
+            }
+        );
+        assert!(parse_go("depth -1").is_err());
+    }
+}
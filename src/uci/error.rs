@@ -0,0 +1,10 @@
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum UciError {
+    #[error("missing value for: {option}.")]
+    MissingValue { option: String },
+
+    #[error("invalid value for: {option}: {value}.")]
+    InvalidValue { option: String, value: String },
+}